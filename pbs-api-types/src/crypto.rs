@@ -1,6 +1,6 @@
 use std::fmt::{self, Display};
 
-use anyhow::Error;
+use anyhow::{bail, Error};
 use serde::{Deserialize, Serialize};
 
 use proxmox_schema::api;
@@ -18,6 +18,23 @@ pub enum CryptMode {
     SignOnly,
 }
 
+impl std::str::FromStr for CryptMode {
+    type Err = Error;
+
+    /// Parse a crypt mode, ignoring case, dashes and underscores, so `sign-only`, `sign_only`,
+    /// `signonly` and `SignOnly` are all accepted.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut mode = s.to_ascii_lowercase();
+        mode.retain(|c| c != '-' && c != '_');
+        match mode.as_str() {
+            "none" => Ok(CryptMode::None),
+            "encrypt" => Ok(CryptMode::Encrypt),
+            "signonly" => Ok(CryptMode::SignOnly),
+            _ => bail!("unknown crypt mode '{s}'"),
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Deserialize, Serialize)]
 #[serde(transparent)]
 /// 32-byte fingerprint, usually calculated with SHA256.
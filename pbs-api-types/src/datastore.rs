@@ -56,6 +56,12 @@ pub const BACKUP_GROUP_FORMAT: ApiStringFormat = ApiStringFormat::Pattern(&GROUP
 pub const BACKUP_NAMESPACE_FORMAT: ApiStringFormat =
     ApiStringFormat::Pattern(&BACKUP_NAMESPACE_REGEX);
 
+/// Maximum length of a backup ID accepted when creating a new backup group.
+///
+/// Neither part of [`BACKUP_ID_REGEX`] nor [`BACKUP_ID_SCHEMA`], so existing groups with longer
+/// IDs can still be listed and managed.
+pub const BACKUP_ID_MAX_LENGTH: usize = 64;
+
 pub const BACKUP_ID_SCHEMA: Schema = StringSchema::new("Backup ID.")
     .format(&BACKUP_ID_FORMAT)
     .schema();
@@ -814,7 +820,7 @@ pub enum BackupType {
 
     /// "Host" backups.
     Host,
-    // NOTE: if you add new types, don't forget to adapt the iter below!
+    // NOTE: if you add new types, don't forget to adapt `all()` below!
 }
 
 impl BackupType {
@@ -835,11 +841,15 @@ impl BackupType {
         }
     }
 
+    /// All backup types.
+    #[inline]
+    pub const fn all() -> &'static [BackupType] {
+        &[BackupType::Vm, BackupType::Ct, BackupType::Host]
+    }
+
     #[inline]
     pub fn iter() -> impl Iterator<Item = BackupType> + Send + Sync + Unpin + 'static {
-        [BackupType::Vm, BackupType::Ct, BackupType::Host]
-            .iter()
-            .copied()
+        Self::all().iter().copied()
     }
 }
 
@@ -901,6 +911,16 @@ impl BackupGroup {
         Self { ty, id: id.into() }
     }
 
+    /// Check that the backup id is valid.
+    ///
+    /// The backup type is always valid since it can only hold known [`BackupType`] variants.
+    pub fn validate(&self) -> Result<(), Error> {
+        if !BACKUP_ID_REGEX.is_match(&self.id) {
+            bail!("invalid backup id '{}'", self.id);
+        }
+        Ok(())
+    }
+
     pub fn matches(&self, filter: &crate::GroupFilter) -> bool {
         use crate::FilterType;
         match &filter.filter_type {
@@ -1219,6 +1239,46 @@ pub struct GroupListItem {
     pub comment: Option<String>,
 }
 
+#[api(
+    properties: {
+        "backup": { type: BackupDir },
+        ns: {
+            type: BackupNamespace,
+            optional: true,
+        },
+    },
+)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// A file of a backup snapshot and its size.
+pub struct SnapshotFileSize {
+    #[serde(flatten)]
+    pub backup: BackupDir,
+    #[serde(skip_serializing_if = "BackupNamespace::is_root", default)]
+    pub ns: BackupNamespace,
+    /// The file name inside the snapshot directory
+    pub filename: String,
+    /// File size in bytes
+    pub size: u64,
+}
+
+#[api()]
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// Aggregated statistics about the snapshots of a backup group.
+pub struct GroupStats {
+    /// Number of contained snapshots
+    pub snapshot_count: usize,
+    /// Sum of the file sizes of all snapshots
+    pub total_size: u64,
+    /// Backup time of the oldest snapshot (0 if the group is empty)
+    pub oldest_snapshot: i64,
+    /// Backup time of the newest snapshot (0 if the group is empty)
+    pub newest_snapshot: i64,
+    /// Set if at least one snapshot has no manifest yet
+    pub has_unfinished: bool,
+}
+
 #[api()]
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
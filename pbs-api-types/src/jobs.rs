@@ -676,6 +676,18 @@ impl KeepOptions {
     }
 }
 
+impl TryFrom<serde_json::Value> for KeepOptions {
+    type Error = anyhow::Error;
+
+    /// Parse keep options from JSON, e.g. read from a file.
+    ///
+    /// The value is verified against the schema first, so errors name the offending property.
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        Self::API_SCHEMA.verify_json(&value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
 #[api(
     properties: {
         keep: {
@@ -711,6 +723,16 @@ impl PruneJobOptions {
         self.keep.keeps_something()
     }
 
+    /// Check for settings which are valid but most likely not what the user intended.
+    ///
+    /// A prune job without any (non-zero) keep option keeps all backups and is never run.
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if !self.keeps_something() {
+            bail!("no keep option set, the prune job would keep all backups and never run");
+        }
+        Ok(())
+    }
+
     pub fn acl_path<'a>(&'a self, store: &'a str) -> Vec<&'a str> {
         match &self.ns {
             Some(ns) => ns.acl_path(store),
@@ -161,6 +161,12 @@ const_regex! {
     );
 
      pub SUBSCRIPTION_KEY_REGEX = concat!(r"^pbs(?:[cbsp])-[0-9a-f]{10}$");
+
+    // simplified RFC 5321 form: dot-atom local-part '@' domain
+    pub EMAIL_REGEX = concatcp!(
+        r"^[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+(?:\.[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+)*@",
+        DNS_NAME_STR, "$"
+    );
 }
 
 pub const PVE_CONFIG_DIGEST_FORMAT: ApiStringFormat = ApiStringFormat::Pattern(&SHA256_HEX_REGEX);
@@ -171,8 +177,43 @@ pub const SUBSCRIPTION_KEY_FORMAT: ApiStringFormat =
 pub const OPENSSL_CIPHERS_TLS_FORMAT: ApiStringFormat =
     ApiStringFormat::Pattern(&OPENSSL_CIPHERS_REGEX);
 
-pub const DAILY_DURATION_FORMAT: ApiStringFormat =
-    ApiStringFormat::VerifyFn(|s| parse_daily_duration(s).map(drop));
+pub const EMAIL_FORMAT: ApiStringFormat = ApiStringFormat::Pattern(&EMAIL_REGEX);
+
+/// Error returned by [`DAILY_DURATION_FORMAT`] verification, includes an example of a valid value.
+#[derive(Debug)]
+pub struct DailyDurationParseError {
+    /// The rejected value
+    pub value: String,
+    /// An example of a valid daily duration
+    pub expected: &'static str,
+    error: anyhow::Error,
+}
+
+impl std::error::Error for DailyDurationParseError {}
+
+impl std::fmt::Display for DailyDurationParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "unable to parse daily duration '{}' - {} (expected something like '{}')",
+            self.value, self.error, self.expected,
+        )
+    }
+}
+
+fn verify_daily_duration(s: &str) -> Result<(), anyhow::Error> {
+    match parse_daily_duration(s) {
+        Ok(_) => Ok(()),
+        Err(error) => Err(DailyDurationParseError {
+            value: s.to_string(),
+            expected: "mon..fri 8:00-17:00",
+            error,
+        }
+        .into()),
+    }
+}
+
+pub const DAILY_DURATION_FORMAT: ApiStringFormat = ApiStringFormat::VerifyFn(verify_daily_duration);
 
 pub const SEARCH_DOMAIN_SCHEMA: Schema =
     StringSchema::new("Search domain for host-name lookup.").schema();
@@ -243,6 +284,16 @@ pub struct StorageStatus {
     pub avail: u64,
 }
 
+impl StorageStatus {
+    /// Returns the used space in percent of the total space, or 0.0 if the total is unknown.
+    pub fn usage_percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.used as f64 / self.total as f64) * 100.0
+    }
+}
+
 pub const PASSWORD_HINT_SCHEMA: Schema = StringSchema::new("Password hint.")
     .format(&SINGLE_LINE_COMMENT_FORMAT)
     .min_length(1)
@@ -288,8 +339,11 @@ pub enum NodePowerCommand {
     Shutdown,
 }
 
+serde_plain::derive_display_from_serialize!(NodePowerCommand);
+serde_plain::derive_fromstr_from_deserialize!(NodePowerCommand);
+
 #[api()]
-#[derive(Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskStateType {
     /// Ok
@@ -333,9 +387,46 @@ pub struct TaskListItem {
     pub status: Option<String>,
 }
 
+impl TaskListItem {
+    /// Returns the backup group of a `backup` task.
+    ///
+    /// Backup tasks use `{store}:{type}/{id}` as worker ID, other task types return `None`.
+    pub fn backup_group(&self) -> Option<BackupGroup> {
+        if self.worker_type != "backup" {
+            return None;
+        }
+        let (_store, group) = self.worker_id.as_deref()?.split_once(':')?;
+        group.parse().ok()
+    }
+}
+
+#[api(
+    properties: {
+        item: {
+            type: TaskListItem,
+        },
+        state: {
+            type: TaskStateType,
+            optional: true,
+        },
+    },
+)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+/// Task properties, including the computed task state.
+pub struct TaskSummary {
+    #[serde(flatten)]
+    pub item: TaskListItem,
+    /// The task state derived from the status (not set while the task is running)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<TaskStateType>,
+    /// The task duration in seconds (not set while the task is running)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<i64>,
+}
+
 pub const NODE_TASKS_LIST_TASKS_RETURN_TYPE: ReturnType = ReturnType {
     optional: false,
-    schema: &ArraySchema::new("A list of tasks.", &TaskListItem::API_SCHEMA).schema(),
+    schema: &ArraySchema::new("A list of tasks.", &TaskSummary::API_SCHEMA).schema(),
 };
 
 #[api()]
@@ -368,6 +459,44 @@ pub enum RRDTimeFrame {
     Decade,
 }
 
+impl RRDTimeFrame {
+    /// Length of the time window covered by this frame, in seconds.
+    pub const fn duration_secs(&self) -> u64 {
+        match self {
+            RRDTimeFrame::Hour => 3600,
+            RRDTimeFrame::Day => 3600 * 24,
+            RRDTimeFrame::Week => 3600 * 24 * 7,
+            RRDTimeFrame::Month => 3600 * 24 * 30,
+            RRDTimeFrame::Year => 3600 * 24 * 365,
+            RRDTimeFrame::Decade => 10 * 3600 * 24 * 366,
+        }
+    }
+
+    /// The next finer time frame, used to drill down (e.g. `Year` -> `Month`).
+    pub const fn prev_frame(&self) -> Option<RRDTimeFrame> {
+        match self {
+            RRDTimeFrame::Hour => None,
+            RRDTimeFrame::Day => Some(RRDTimeFrame::Hour),
+            RRDTimeFrame::Week => Some(RRDTimeFrame::Day),
+            RRDTimeFrame::Month => Some(RRDTimeFrame::Week),
+            RRDTimeFrame::Year => Some(RRDTimeFrame::Month),
+            RRDTimeFrame::Decade => Some(RRDTimeFrame::Year),
+        }
+    }
+
+    /// The next coarser time frame (e.g. `Month` -> `Year`).
+    pub const fn next_frame(&self) -> Option<RRDTimeFrame> {
+        match self {
+            RRDTimeFrame::Hour => Some(RRDTimeFrame::Day),
+            RRDTimeFrame::Day => Some(RRDTimeFrame::Week),
+            RRDTimeFrame::Week => Some(RRDTimeFrame::Month),
+            RRDTimeFrame::Month => Some(RRDTimeFrame::Year),
+            RRDTimeFrame::Year => Some(RRDTimeFrame::Decade),
+            RRDTimeFrame::Decade => None,
+        }
+    }
+}
+
 #[api]
 #[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
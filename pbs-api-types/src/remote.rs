@@ -104,3 +104,20 @@ pub struct RemoteWithoutPassword {
     #[serde(flatten)]
     pub config: RemoteConfig,
 }
+
+#[api]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// Result of a remote connection test.
+pub struct RemoteConnectionStatus {
+    /// Whether the remote host answered at all
+    pub reachable: bool,
+    /// Whether the configured credentials were accepted
+    pub auth_ok: bool,
+    /// The version reported by the remote
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Round trip time of the version request in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+}
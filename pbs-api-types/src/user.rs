@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use proxmox_schema::{api, BooleanSchema, IntegerSchema, Schema, StringSchema, Updater};
 
 use super::userid::{Authid, Userid, PROXMOX_TOKEN_ID_SCHEMA};
-use super::{SINGLE_LINE_COMMENT_FORMAT, SINGLE_LINE_COMMENT_SCHEMA};
+use super::{EMAIL_FORMAT, SINGLE_LINE_COMMENT_FORMAT, SINGLE_LINE_COMMENT_SCHEMA};
 
 pub const ENABLE_USER_SCHEMA: Schema = BooleanSchema::new(
     "Enable the account (default). You can set this to '0' to disable the account.",
@@ -36,6 +36,16 @@ pub const EMAIL_SCHEMA: Schema = StringSchema::new("E-Mail Address.")
     .max_length(64)
     .schema();
 
+/// Stricter variant of [`EMAIL_SCHEMA`] for addresses entered via the user create/update API.
+///
+/// Stored configs and addresses from external sources (LDAP, OpenID) keep using the lenient
+/// [`EMAIL_SCHEMA`], so existing entries stay valid.
+pub const USER_EMAIL_INPUT_SCHEMA: Schema = StringSchema::new("E-Mail Address.")
+    .format(&EMAIL_FORMAT)
+    .min_length(2)
+    .max_length(64)
+    .schema();
+
 #[api(
     properties: {
         userid: {
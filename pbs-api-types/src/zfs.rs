@@ -76,3 +76,33 @@ pub struct ZpoolListItem {
     /// ZFS deduplication ratio
     pub dedup: f64,
 }
+
+impl ZpoolListItem {
+    /// Returns the used space (bytes).
+    pub fn used_bytes(&self) -> u64 {
+        self.alloc
+    }
+
+    /// Returns the available space (bytes).
+    pub fn available_bytes(&self) -> u64 {
+        self.free
+    }
+
+    /// Returns the used space in percent of the total pool size.
+    pub fn used_percent(&self) -> f64 {
+        if self.size == 0 {
+            return 0.0;
+        }
+        (self.used_bytes() as f64 * 100.0) / self.size as f64
+    }
+}
+
+impl From<ZpoolListItem> for crate::StorageStatus {
+    fn from(item: ZpoolListItem) -> Self {
+        Self {
+            total: item.size,
+            used: item.used_bytes(),
+            avail: item.available_bytes(),
+        }
+    }
+}
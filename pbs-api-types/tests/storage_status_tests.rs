@@ -0,0 +1,28 @@
+use pbs_api_types::StorageStatus;
+
+#[test]
+fn test_usage_percent() {
+    let status = StorageStatus {
+        total: 200,
+        used: 50,
+        avail: 150,
+    };
+    assert_eq!(status.usage_percent(), 25.0);
+
+    let full = StorageStatus {
+        total: 200,
+        used: 200,
+        avail: 0,
+    };
+    assert_eq!(full.usage_percent(), 100.0);
+}
+
+#[test]
+fn test_usage_percent_unknown_total() {
+    let status = StorageStatus {
+        total: 0,
+        used: 0,
+        avail: 0,
+    };
+    assert_eq!(status.usage_percent(), 0.0);
+}
@@ -158,7 +158,7 @@ impl BackupReader {
 
         tmpfile.seek(SeekFrom::Start(0))?;
         let (csum, size) = sha256(&mut tmpfile)?;
-        manifest.verify_file(name, &csum, size)?;
+        manifest.ensure_file(name, &csum, size)?;
 
         tmpfile.seek(SeekFrom::Start(0))?;
 
@@ -187,7 +187,7 @@ impl BackupReader {
 
         // Note: do not use values stored in index (not trusted) - instead, computed them again
         let (csum, size) = index.compute_csum();
-        manifest.verify_file(name, &csum, size)?;
+        manifest.ensure_file(name, &csum, size)?;
 
         Ok(index)
     }
@@ -214,7 +214,7 @@ impl BackupReader {
 
         // Note: do not use values stored in index (not trusted) - instead, computed them again
         let (csum, size) = index.compute_csum();
-        manifest.verify_file(name, &csum, size)?;
+        manifest.ensure_file(name, &csum, size)?;
 
         Ok(index)
     }
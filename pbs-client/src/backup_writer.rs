@@ -85,6 +85,7 @@ impl BackupWriter {
         backup: &BackupDir,
         debug: bool,
         benchmark: bool,
+        allow_future_time: bool,
     ) -> Result<Arc<BackupWriter>, Error> {
         let mut param = json!({
             "backup-type": backup.ty(),
@@ -99,6 +100,11 @@ impl BackupWriter {
             param["ns"] = serde_json::to_value(ns)?;
         }
 
+        // only send when set, older servers do not know this parameter
+        if allow_future_time {
+            param["allow-future-time"] = true.into();
+        }
+
         let req = HttpClient::request_builder(
             client.server(),
             client.port(),
@@ -284,11 +290,7 @@ impl BackupWriter {
         let close_path = format!("{}_close", prefix);
 
         if let Some(manifest) = options.previous_manifest {
-            if !manifest
-                .files()
-                .iter()
-                .any(|file| file.filename == archive_name)
-            {
+            if !manifest.files().any(|file| file.filename() == archive_name) {
                 log::info!("Previous manifest does not contain an archive called '{archive_name}', skipping download..");
             } else {
                 // try, but ignore errors
@@ -539,7 +541,7 @@ impl BackupWriter {
         })?;
         // Note: do not use values stored in index (not trusted) - instead, computed them again
         let (csum, size) = index.compute_csum();
-        manifest.verify_file(archive_name, &csum, size)?;
+        manifest.ensure_file(archive_name, &csum, size)?;
 
         // add index chunks to known chunks
         let mut known_chunks = known_chunks.lock().unwrap();
@@ -577,7 +579,7 @@ impl BackupWriter {
             .map_err(|err| format_err!("unable to read dynamic index '{archive_name}' - {err}"))?;
         // Note: do not use values stored in index (not trusted) - instead, computed them again
         let (csum, size) = index.compute_csum();
-        manifest.verify_file(archive_name, &csum, size)?;
+        manifest.ensure_file(archive_name, &csum, size)?;
 
         // add index chunks to known chunks
         let mut known_chunks = known_chunks.lock().unwrap();
@@ -32,6 +32,18 @@ pub const MASTER_PUBKEY_FD_SCHEMA: Schema =
         .minimum(0)
         .schema();
 
+/// Like the [CryptMode] schema, but ignoring case, dashes and underscores (see its `FromStr`).
+pub const CRYPT_MODE_SCHEMA: Schema = StringSchema::new(
+    "Defines whether data is encrypted (using an AEAD cipher), only signed, or neither. \
+    One of 'none', 'encrypt' or 'sign-only', case, dashes and underscores are ignored.",
+)
+.format(&ApiStringFormat::VerifyFn(verify_crypt_mode))
+.schema();
+
+fn verify_crypt_mode(mode: &str) -> Result<(), Error> {
+    mode.parse::<CryptMode>().map(|_| ())
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum KeySource {
     DefaultKey,
@@ -132,7 +144,8 @@ fn do_crypto_parameters(param: &Value, keep_keyfd_open: bool) -> Result<CryptoPa
     };
 
     let mode: Option<CryptMode> = match param.get("crypt-mode") {
-        Some(mode) => Some(serde::Deserialize::deserialize(mode)?),
+        Some(Value::String(mode)) => Some(mode.parse()?),
+        Some(_) => bail!("bad --crypt-mode parameter type"),
         None => None,
     };
 
@@ -475,6 +488,8 @@ fn test_crypto_parameters_handling() -> Result<(), Error> {
     assert_eq!(res.unwrap(), some_key_sign_res);
     let res = crypto_parameters(&json!({"crypt-mode": "encrypt", "keyfile": keypath}));
     assert_eq!(res.unwrap(), some_key_res);
+    let res = crypto_parameters(&json!({"crypt-mode": "SignOnly", "keyfile": keypath}));
+    assert_eq!(res.unwrap(), some_key_sign_res);
 
     // invalid keyfile parameter always errors
     assert!(crypto_parameters(&json!({ "keyfile": invalid_keypath })).is_err());
@@ -642,3 +657,22 @@ fn test_crypto_parameters_handling() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_crypt_mode_cli_schema() -> Result<(), Error> {
+    // the CLI checks parameters against the schema before crypto_parameters parses them
+    for (input, expected) in [
+        ("none", CryptMode::None),
+        ("Encrypt", CryptMode::Encrypt),
+        ("sign-only", CryptMode::SignOnly),
+        ("SignOnly", CryptMode::SignOnly),
+        ("SIGN_ONLY", CryptMode::SignOnly),
+    ] {
+        let value = CRYPT_MODE_SCHEMA.parse_simple_value(input)?;
+        assert_eq!(value.as_str().unwrap().parse::<CryptMode>()?, expected);
+    }
+    assert!(CRYPT_MODE_SCHEMA.parse_simple_value("sign only").is_err());
+    assert!(CRYPT_MODE_SCHEMA.parse_simple_value("signed").is_err());
+
+    Ok(())
+}
@@ -1,19 +1,22 @@
 use std::fmt;
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{bail, format_err, Error};
 
-use proxmox_sys::fs::{lock_dir_noblock, replace_file, CreateOptions};
+use proxmox_sys::fs::{lock_dir_noblock, lock_dir_noblock_shared, replace_file, CreateOptions};
 
 use pbs_api_types::{
     Authid, BackupNamespace, BackupType, GroupFilter, BACKUP_DATE_REGEX, BACKUP_FILE_REGEX,
 };
 use pbs_config::{open_backup_lockfile, BackupLockGuard};
 
+use crate::index::IndexFile;
 use crate::manifest::{
-    BackupManifest, CLIENT_LOG_BLOB_NAME, MANIFEST_BLOB_NAME, MANIFEST_LOCK_NAME,
+    archive_type, ArchiveType, BackupManifest, CLIENT_LOG_BLOB_NAME, MANIFEST_BLOB_NAME,
+    MANIFEST_LOCK_NAME,
 };
 use crate::{DataBlob, DataStore};
 
@@ -47,6 +50,15 @@ impl BackupGroupDeleteStats {
     }
 }
 
+/// Result of comparing a list of (remote) snapshots against the local group contents.
+#[derive(Debug, Default)]
+pub struct MergeStats {
+    /// Count of snapshots which already exist locally and are finished
+    pub already_present: usize,
+    /// Snapshots which are missing locally or exist but are not finished
+    pub missing: Vec<pbs_api_types::BackupDir>,
+}
+
 /// BackupGroup is a directory containing a list of BackupDir
 #[derive(Clone)]
 pub struct BackupGroup {
@@ -67,12 +79,16 @@ impl fmt::Debug for BackupGroup {
 }
 
 impl BackupGroup {
+    /// Any id read from the datastore is valid, but ids from other sources (e.g. a remote's
+    /// group list) are not necessarily schema checked, so make sure it cannot escape the
+    /// datastore.
     pub(crate) fn new(
         store: Arc<DataStore>,
         ns: BackupNamespace,
         group: pbs_api_types::BackupGroup,
-    ) -> Self {
-        Self { store, ns, group }
+    ) -> Result<Self, Error> {
+        group.validate()?;
+        Ok(Self { store, ns, group })
     }
 
     /// Access the underlying [`BackupGroup`](pbs_api_types::BackupGroup).
@@ -114,6 +130,14 @@ impl BackupGroup {
     }
 
     pub fn list_backups(&self) -> Result<Vec<BackupInfo>, Error> {
+        self.list_backups_since(i64::MIN)
+    }
+
+    /// List all backups with a backup time at or after `cutoff` (epoch).
+    ///
+    /// The backup time is parsed from the directory name, so older snapshots are skipped without
+    /// looking at their contents.
+    pub fn list_backups_since(&self, cutoff: i64) -> Result<Vec<BackupInfo>, Error> {
         let mut list = vec![];
 
         let path = self.full_group_path();
@@ -127,6 +151,10 @@ impl BackupGroup {
                     return Ok(());
                 }
 
+                if proxmox_time::parse_rfc3339(backup_time)? < cutoff {
+                    return Ok(());
+                }
+
                 let backup_dir = self.backup_dir_with_rfc3339(backup_time)?;
                 let files = list_backup_files(l2_fd, backup_time)?;
 
@@ -145,15 +173,29 @@ impl BackupGroup {
     }
 
     /// Finds the latest backup inside a backup group
-    pub fn last_backup(&self, only_finished: bool) -> Result<Option<BackupInfo>, Error> {
+    ///
+    /// If `filter` is set, only backups for which it returns true are considered.
+    pub fn last_backup(
+        &self,
+        filter: Option<&dyn Fn(&BackupInfo) -> bool>,
+    ) -> Result<Option<BackupInfo>, Error> {
         let backups = self.list_backups()?;
         Ok(backups
             .into_iter()
-            .filter(|item| !only_finished || item.is_finished())
+            .filter(|item| filter.map_or(true, |filter| filter(item)))
             .max_by_key(|item| item.backup_dir.backup_time()))
     }
 
-    pub fn last_successful_backup(&self) -> Result<Option<i64>, Error> {
+    /// Finds the latest finished backup inside a backup group
+    pub fn last_finished_backup(&self) -> Result<Option<BackupInfo>, Error> {
+        self.last_backup(Some(&BackupInfo::is_finished))
+    }
+
+    /// Finds the latest snapshot with a manifest inside a backup group
+    ///
+    /// Only the manifest's existence is checked for each snapshot, the file list is only read for
+    /// the returned one.
+    pub fn last_successful_backup(&self) -> Result<Option<BackupInfo>, Error> {
         let mut last = None;
 
         let path = self.full_group_path();
@@ -203,7 +245,44 @@ impl BackupGroup {
             },
         )?;
 
-        Ok(last)
+        last.map(|time| BackupInfo::new(self.backup_dir(time)?))
+            .transpose()
+    }
+
+    /// Compare `remote_snapshots` against the local contents of this group.
+    ///
+    /// Snapshots which exist locally but are not finished are reported as missing. This does not
+    /// modify the filesystem, transferring the missing snapshots is up to the caller.
+    pub fn merge(
+        &self,
+        remote_snapshots: &[pbs_api_types::BackupDir],
+    ) -> Result<MergeStats, Error> {
+        let mut finished = std::collections::HashSet::new();
+        if self.exists() {
+            for info in self.list_backups()? {
+                if info.is_finished() {
+                    finished.insert(info.backup_dir.backup_time());
+                }
+            }
+        }
+
+        let mut stats = MergeStats::default();
+        for snapshot in remote_snapshots {
+            if snapshot.group != self.group {
+                bail!(
+                    "snapshot {} does not belong to group {}",
+                    snapshot,
+                    self.group
+                );
+            }
+            if finished.contains(&snapshot.time) {
+                stats.already_present += 1;
+            } else {
+                stats.missing.push(snapshot.clone());
+            }
+        }
+
+        Ok(stats)
     }
 
     pub fn matches(&self, filter: &GroupFilter) -> bool {
@@ -225,6 +304,27 @@ impl BackupGroup {
         crate::ListSnapshots::new(self.clone())
     }
 
+    /// Check whether the group directory is currently locked, e.g. by a running backup.
+    ///
+    /// This tries to take the same non-blocking exclusive lock `lock_dir_noblock` would take and
+    /// releases it again right away. Returns `true` if the lock is held by someone else.
+    pub fn has_running_backup(&self) -> Result<bool, Error> {
+        use nix::fcntl::{flock, FlockArg, OFlag};
+        use nix::sys::stat::Mode;
+
+        let path = self.full_group_path();
+        let dir = nix::dir::Dir::open(&path, OFlag::O_RDONLY, Mode::empty()).map_err(|err| {
+            format_err!("unable to open backup group directory {path:?} for locking - {err}")
+        })?;
+
+        // the lock is released when `dir` is closed
+        match flock(dir.as_raw_fd(), FlockArg::LockExclusiveNonblock) {
+            Ok(()) => Ok(false),
+            Err(nix::errno::Errno::EWOULDBLOCK) => Ok(true),
+            Err(err) => bail!("unable to check lock on backup group directory {path:?} - {err}"),
+        }
+    }
+
     /// Destroy the group inclusive all its backup snapshots (BackupDir's)
     ///
     /// Returns `BackupGroupDeleteStats`, containing the number of deleted snapshots
@@ -269,6 +369,21 @@ impl BackupGroup {
     }
 }
 
+/// Iterate over the snapshots of a group, see [BackupGroup::iter_snapshots].
+///
+/// An error opening the group directory is returned as the only item.
+impl<'a> IntoIterator for &'a BackupGroup {
+    type Item = Result<BackupInfo, Error>;
+    type IntoIter = Box<dyn Iterator<Item = Result<BackupInfo, Error>> + 'static>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self.iter_snapshots() {
+            Ok(list) => Box::new(list.map(|snapshot| BackupInfo::new(snapshot?))),
+            Err(err) => Box::new(std::iter::once(Err(err))),
+        }
+    }
+}
+
 impl AsRef<pbs_api_types::BackupNamespace> for BackupGroup {
     #[inline]
     fn as_ref(&self) -> &pbs_api_types::BackupNamespace {
@@ -393,6 +508,18 @@ impl BackupDir {
         self.dir.time
     }
 
+    /// Returns the number of seconds since the snapshot was created.
+    ///
+    /// Negative if the backup time lies in the future.
+    pub fn snapshot_age_secs(&self) -> i64 {
+        proxmox_time::epoch_i64() - self.backup_time()
+    }
+
+    /// Returns the time since the snapshot was created, zero if it lies in the future.
+    pub fn snapshot_age_duration(&self) -> Duration {
+        Duration::from_secs(self.snapshot_age_secs().max(0) as u64)
+    }
+
     pub fn backup_time_string(&self) -> &str {
         &self.backup_time_string
     }
@@ -451,8 +578,7 @@ impl BackupDir {
     /// Also creates the basedir. The lockfile is located in
     /// '/run/proxmox-backup/locks/{datastore}/[ns/{ns}/]+{type}/{id}/{timestamp}.index.json.lck'
     fn manifest_lock_path(&self) -> Result<PathBuf, Error> {
-        let mut path = PathBuf::from(&format!("/run/proxmox-backup/locks/{}", self.store.name()));
-        path.push(self.relative_path());
+        let mut path = self.manifest_lock_dir();
 
         std::fs::create_dir_all(&path)?;
         let ts = self.backup_time_string();
@@ -461,19 +587,78 @@ impl BackupDir {
         Ok(path)
     }
 
+    fn manifest_lock_dir(&self) -> PathBuf {
+        let mut path = self.store.manifest_lock_base_path();
+        path.push(self.relative_path());
+        path
+    }
+
+    /// Removes the manifest lock file of this snapshot if it was orphaned.
+    ///
+    /// The lock file is only considered orphaned if the snapshot itself does not exist anymore
+    /// and nobody holds the lock, e.g. after a crash during the removal of the snapshot.
+    ///
+    /// Returns the number of removed lock files.
+    pub fn cleanup_lock_files(&self) -> Result<usize, Error> {
+        use nix::fcntl::{flock, FlockArg};
+
+        if self.full_path().exists() {
+            return Ok(0);
+        }
+
+        let mut path = self.manifest_lock_dir();
+        let ts = self.backup_time_string();
+        path.push(&format!("{ts}{MANIFEST_LOCK_NAME}"));
+
+        let file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => bail!("unable to open manifest lock {path:?} - {err}"),
+        };
+
+        // the lock is released when `file` is closed
+        match flock(file.as_raw_fd(), FlockArg::LockExclusiveNonblock) {
+            Ok(()) => (),
+            Err(nix::errno::Errno::EWOULDBLOCK) => return Ok(0),
+            Err(err) => bail!("unable to check manifest lock {path:?} - {err}"),
+        }
+
+        // the snapshot could have been created before we got the lock
+        if self.full_path().exists() {
+            return Ok(0);
+        }
+
+        // only remove the file we actually locked, it might have been replaced in the meantime
+        let locked = nix::sys::stat::fstat(file.as_raw_fd())
+            .map_err(|err| format_err!("unable to stat manifest lock {path:?} - {err}"))?;
+        match nix::sys::stat::stat(&path) {
+            Ok(stat) if stat.st_dev == locked.st_dev && stat.st_ino == locked.st_ino => (),
+            Ok(_) | Err(nix::errno::Errno::ENOENT) => return Ok(0),
+            Err(err) => bail!("unable to stat manifest lock {path:?} - {err}"),
+        }
+
+        std::fs::remove_file(&path)
+            .map_err(|err| format_err!("unable to remove manifest lock {path:?} - {err}"))?;
+
+        Ok(1)
+    }
+
     /// Locks the manifest of a snapshot, for example, to update or delete it.
     pub(crate) fn lock_manifest(&self) -> Result<BackupLockGuard, Error> {
         let path = self.manifest_lock_path()?;
 
         // actions locking the manifest should be relatively short, only wait a few seconds
-        open_backup_lockfile(&path, Some(std::time::Duration::from_secs(5)), true)
+        open_backup_lockfile(&path, Some(Duration::from_secs(5)), true)
             .map_err(|err| format_err!("unable to acquire manifest lock {:?} - {}", &path, err))
     }
 
     /// Destroy the whole snapshot, bails if it's protected
     ///
     /// Setting `force` to true skips locking and thus ignores if the backup is currently in use.
-    pub fn destroy(&self, force: bool) -> Result<(), Error> {
+    ///
+    /// Returns the total size of the removed files. Note that this only covers the snapshot
+    /// directory itself, chunks are only freed by a later garbage collection.
+    pub fn destroy(&self, force: bool) -> Result<u64, Error> {
         let full_path = self.full_path();
 
         let (_guard, _manifest_guard);
@@ -486,6 +671,14 @@ impl BackupDir {
             bail!("cannot remove protected snapshot"); // use special error type?
         }
 
+        // files we cannot stat are not counted, that must not prevent the removal
+        let size = std::fs::read_dir(&full_path)
+            .map_err(|err| format_err!("unable to read snapshot dir {full_path:?} - {err}"))?
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len())
+            .sum();
+
         log::info!("removing backup snapshot {:?}", full_path);
         std::fs::remove_dir_all(&full_path).map_err(|err| {
             format_err!("removing backup snapshot {:?} failed - {}", full_path, err,)
@@ -496,7 +689,7 @@ impl BackupDir {
             let _ = std::fs::remove_file(path); // ignore errors
         }
 
-        Ok(())
+        Ok(size)
     }
 
     /// Get the datastore.
@@ -548,6 +741,133 @@ impl BackupDir {
         Ok(())
     }
 
+    /// Clone this snapshot into namespace `target_ns` of `target_store`.
+    ///
+    /// All chunks referenced by the snapshot's indices get hard-linked into the target chunk store
+    /// (or copied, if the chunk stores are located on different file systems), afterwards the
+    /// snapshot's files and manifest are copied over.
+    pub fn clone_to_namespace(
+        &self,
+        target_ns: &BackupNamespace,
+        target_store: &Arc<DataStore>,
+    ) -> Result<BackupDir, Error> {
+        if !target_store.namespace_exists(target_ns) {
+            bail!(
+                "namespace '{}' does not exist on datastore '{}'",
+                target_ns,
+                target_store.name()
+            );
+        }
+
+        // keep a GC on the target store from removing linked chunks before the indices are copied
+        let _shared_store_lock = target_store.try_shared_chunk_store_lock()?;
+
+        let _snap_guard =
+            lock_dir_noblock_shared(&self.full_path(), "snapshot", "locked by another operation")?;
+        let (manifest, _) = self.load_manifest()?;
+
+        let owner = self.get_owner()?;
+        let (_owner, _group_guard) =
+            target_store.create_locked_backup_group(target_ns, self.group(), &owner)?;
+        let (relative_path, is_new, _dir_guard) =
+            target_store.create_locked_backup_dir(target_ns, &self.dir, true)?;
+        if !is_new {
+            bail!(
+                "snapshot {} already exists in namespace '{}'",
+                self.dir,
+                target_ns
+            );
+        }
+
+        let mut target_path = target_store.base_path();
+        target_path.push(&relative_path);
+
+        let result = proxmox_lang::try_block!({
+            let source_path = self.full_path();
+            let same_store = self.store.base_path() == target_store.base_path();
+
+            for item in manifest.files() {
+                let mut source_file = source_path.clone();
+                source_file.push(item.filename());
+
+                if !same_store && archive_type(item.filename())? != ArchiveType::Blob {
+                    let index = self.store.open_index(&source_file)?;
+                    for pos in 0..index.index_count() {
+                        let digest = index.index_digest(pos).unwrap();
+                        self.link_chunk(digest, target_store)?;
+                    }
+                }
+
+                let mut target_file = target_path.clone();
+                target_file.push(item.filename());
+                std::fs::copy(&source_file, &target_file).map_err(|err| {
+                    format_err!(
+                        "copying {:?} to {:?} failed - {}",
+                        source_file,
+                        target_file,
+                        err
+                    )
+                })?;
+            }
+
+            for filename in [CLIENT_LOG_BLOB_NAME, MANIFEST_BLOB_NAME] {
+                let mut source_file = source_path.clone();
+                source_file.push(filename);
+                if !source_file.exists() {
+                    continue;
+                }
+                let mut target_file = target_path.clone();
+                target_file.push(filename);
+                std::fs::copy(&source_file, &target_file).map_err(|err| {
+                    format_err!(
+                        "copying {:?} to {:?} failed - {}",
+                        source_file,
+                        target_file,
+                        err
+                    )
+                })?;
+            }
+
+            Ok(())
+        });
+
+        if let Err(err) = result {
+            let _ = std::fs::remove_dir_all(&target_path); // ignore errors
+            return Err(err);
+        }
+
+        target_store.backup_dir(target_ns.clone(), self.dir.clone())
+    }
+
+    // hard-link a chunk into `target_store`, fall back to copying it across file systems
+    //
+    // The chunk gets touched in any case, a hard link keeps the old atime, which could make a
+    // running GC on the target store remove it.
+    fn link_chunk(&self, digest: &[u8; 32], target_store: &DataStore) -> Result<(), Error> {
+        let (source_chunk, digest_str) = self.store.chunk_path(digest);
+        let (target_chunk, _) = target_store.chunk_path(digest);
+
+        if !target_chunk.exists() {
+            match nix::unistd::linkat(
+                None,
+                &source_chunk,
+                None,
+                &target_chunk,
+                nix::unistd::LinkatFlags::NoSymlinkFollow,
+            ) {
+                Ok(()) | Err(nix::errno::Errno::EEXIST) => {}
+                Err(nix::errno::Errno::EXDEV) => {
+                    let chunk = self.store.load_chunk(digest)?;
+                    target_store.insert_chunk(&chunk, digest)?;
+                }
+                Err(err) => bail!("unable to link chunk {} - {}", digest_str, err),
+            }
+        }
+
+        target_store.cond_touch_chunk(digest, true)?;
+        Ok(())
+    }
+
     /// Cleans up the backup directory by removing any file not mentioned in the manifest.
     pub fn cleanup_unreferenced_files(&self, manifest: &BackupManifest) -> Result<(), Error> {
         let full_path = self.full_path();
@@ -555,8 +875,8 @@ impl BackupDir {
         let mut wanted_files = std::collections::HashSet::new();
         wanted_files.insert(MANIFEST_BLOB_NAME.to_string());
         wanted_files.insert(CLIENT_LOG_BLOB_NAME.to_string());
-        manifest.files().iter().for_each(|item| {
-            wanted_files.insert(item.filename.clone());
+        manifest.files().for_each(|item| {
+            wanted_files.insert(item.filename().to_string());
         });
 
         for item in proxmox_sys::fs::read_subdir(libc::AT_FDCWD, &full_path)?.flatten() {
@@ -636,6 +956,15 @@ pub struct BackupInfo {
     pub protected: bool,
 }
 
+/// Sort criteria for [`BackupInfo::sort_list`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupSortField {
+    /// Sort by backup time
+    Time,
+    /// Sort by the summed up size of the snapshot's files
+    Size,
+}
+
 impl BackupInfo {
     pub fn new(backup_dir: BackupDir) -> Result<BackupInfo, Error> {
         let path = backup_dir.full_path();
@@ -650,16 +979,66 @@ impl BackupInfo {
         })
     }
 
-    pub fn sort_list(list: &mut [BackupInfo], ascendending: bool) {
-        if ascendending {
+    pub fn sort_list(list: &mut [BackupInfo], field: BackupSortField, ascendending: bool) {
+        match (field, ascendending) {
             // oldest first
-            list.sort_unstable_by(|a, b| a.backup_dir.dir.time.cmp(&b.backup_dir.dir.time));
-        } else {
+            (BackupSortField::Time, true) => {
+                list.sort_unstable_by(|a, b| a.backup_dir.dir.time.cmp(&b.backup_dir.dir.time))
+            }
             // newest first
-            list.sort_unstable_by(|a, b| b.backup_dir.dir.time.cmp(&a.backup_dir.dir.time));
+            (BackupSortField::Time, false) => {
+                list.sort_unstable_by(|a, b| b.backup_dir.dir.time.cmp(&a.backup_dir.dir.time))
+            }
+            // sort_by_cached_key only stats the files of each snapshot once, snapshots which
+            // cannot be stat'ed are treated as empty
+            (BackupSortField::Size, true) => {
+                list.sort_by_cached_key(|info| info.total_file_size().unwrap_or(0))
+            }
+            (BackupSortField::Size, false) => list
+                .sort_by_cached_key(|info| std::cmp::Reverse(info.total_file_size().unwrap_or(0))),
         }
     }
 
+    /// Returns the summed up size of all files in the snapshot directory.
+    ///
+    /// This does not include the chunks referenced by index files.
+    pub fn total_file_size(&self) -> Result<u64, Error> {
+        Ok(self.file_sizes()?.iter().map(|(_, size)| size).sum())
+    }
+
+    /// Stat the files of this snapshot and return them sorted by descending size.
+    ///
+    /// Files that vanished in the meantime are skipped.
+    pub fn files_sorted_by_size(&self) -> Result<Vec<(String, u64)>, Error> {
+        let mut list = self.file_sizes()?;
+        list.sort_unstable_by(|(_, a), (_, b)| b.cmp(a));
+        Ok(list)
+    }
+
+    /// Return the files of this snapshot with a size of at least `min_size` bytes, sorted by
+    /// descending size.
+    pub fn list_large_files(&self, min_size: u64) -> Result<Vec<(String, u64)>, Error> {
+        let mut list = self.files_sorted_by_size()?;
+        list.retain(|(_, size)| *size >= min_size);
+        Ok(list)
+    }
+
+    // stat all files of the snapshot, skipping vanished ones
+    fn file_sizes(&self) -> Result<Vec<(String, u64)>, Error> {
+        let mut path = self.backup_dir.full_path();
+        let mut list = Vec::with_capacity(self.files.len());
+        for file in &self.files {
+            path.push(file);
+            match std::fs::metadata(&path) {
+                Ok(metadata) => list.push((file.clone(), metadata.len())),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
+                Err(err) => bail!("unable to stat file {:?} - {}", path, err),
+            }
+            path.pop();
+        }
+        Ok(list)
+    }
+
     pub fn is_finished(&self) -> bool {
         // backup is considered unfinished if there is no manifest
         self.files.iter().any(|name| name == MANIFEST_BLOB_NAME)
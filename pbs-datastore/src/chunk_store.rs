@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::os::unix::io::AsRawFd;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
@@ -28,6 +29,30 @@ pub struct ChunkStore {
     sync_level: DatastoreFSyncLevel,
 }
 
+/// Chunks marked by a garbage collection dry-run, which must not touch them
+#[derive(Default)]
+pub struct GcMarkedChunks {
+    /// Existing chunks referenced by an index
+    pub chunks: HashSet<[u8; 32]>,
+    /// Referenced chunks which are missing, their `.bad` files are kept
+    pub missing: HashSet<[u8; 32]>,
+}
+
+impl GcMarkedChunks {
+    // check if a chunk file (`<digest>` or `<digest>.<n>.bad`) was marked
+    fn contains(&self, filename: &[u8], bad: bool) -> bool {
+        let mut digest = [0u8; 32];
+        if filename.len() < 64 || hex::decode_to_slice(&filename[..64], &mut digest).is_err() {
+            return false;
+        }
+        if bad {
+            self.missing.contains(&digest)
+        } else {
+            self.chunks.contains(&digest)
+        }
+    }
+}
+
 // TODO: what about sysctl setting vm.vfs_cache_pressure (0 - 100) ?
 
 pub fn verify_chunk_size(size: usize) -> Result<(), Error> {
@@ -347,12 +372,17 @@ impl ChunkStore {
         ProcessLocker::oldest_shared_lock(self.locker.clone().unwrap())
     }
 
+    /// Remove all chunks which were not marked (atime updated) during phase 1.
+    ///
+    /// With `dry_run` set, nothing gets unlinked, but `status` is updated as if the chunks had
+    /// been removed. The chunks marked by the dry-run count as recently touched.
     pub fn sweep_unused_chunks(
         &self,
         oldest_writer: i64,
         phase1_start_time: i64,
         status: &mut GarbageCollectionStatus,
         worker: &dyn WorkerTaskContext,
+        dry_run: Option<&GcMarkedChunks>,
     ) -> Result<(), Error> {
         // unwrap: only `None` in unit tests
         assert!(self.locker.is_some());
@@ -401,17 +431,27 @@ impl ChunkStore {
 
                 chunk_count += 1;
 
-                if stat.st_atime < min_atime {
+                // a dry-run does not touch the chunks it marked, treat them as just touched
+                let atime = match dry_run {
+                    Some(marked) if marked.contains(filename.to_bytes(), bad) => i64::MAX,
+                    _ => stat.st_atime,
+                };
+
+                if atime < min_atime {
                     //let age = now - stat.st_atime;
                     //println!("UNLINK {}  {:?}", age/(3600*24), filename);
-                    if let Err(err) = unlinkat(Some(dirfd), filename, UnlinkatFlags::NoRemoveDir) {
-                        if bad {
-                            status.still_bad += 1;
+                    if dry_run.is_none() {
+                        if let Err(err) =
+                            unlinkat(Some(dirfd), filename, UnlinkatFlags::NoRemoveDir)
+                        {
+                            if bad {
+                                status.still_bad += 1;
+                            }
+                            bail!(
+                                "unlinking chunk {filename:?} failed on store '{}' - {err}",
+                                self.name,
+                            );
                         }
-                        bail!(
-                            "unlinking chunk {filename:?} failed on store '{}' - {err}",
-                            self.name,
-                        );
                     }
                     if bad {
                         status.removed_bad += 1;
@@ -419,7 +459,7 @@ impl ChunkStore {
                         status.removed_chunks += 1;
                     }
                     status.removed_bytes += stat.st_size as u64;
-                } else if stat.st_atime < oldest_writer {
+                } else if atime < oldest_writer {
                     if bad {
                         status.still_bad += 1;
                     } else {
@@ -3,6 +3,7 @@ use std::io::{self, Write};
 use std::os::unix::io::AsRawFd;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use anyhow::{bail, format_err, Error};
 use lazy_static::lazy_static;
@@ -12,27 +13,38 @@ use proxmox_human_byte::HumanByte;
 use proxmox_schema::ApiType;
 
 use proxmox_sys::error::SysError;
-use proxmox_sys::fs::{file_read_optional_string, replace_file, CreateOptions};
-use proxmox_sys::fs::{lock_dir_noblock, DirLockGuard};
+use proxmox_sys::fs::{
+    create_dir, create_path, file_read_optional_string, replace_file, CreateOptions,
+};
+use proxmox_sys::fs::{lock_dir_noblock, lock_dir_noblock_shared, DirLockGuard};
 use proxmox_sys::process_locker::ProcessLockSharedGuard;
 use proxmox_sys::WorkerTaskContext;
 use proxmox_sys::{task_log, task_warn};
 
 use pbs_api_types::{
-    Authid, BackupNamespace, BackupType, ChunkOrder, DataStoreConfig, DatastoreFSyncLevel,
-    DatastoreTuning, GarbageCollectionStatus, MaintenanceMode, MaintenanceType, Operation, UPID,
+    Authid, BackupNamespace, BackupType, ChunkOrder, CryptMode, DataStoreConfig,
+    DatastoreFSyncLevel, DatastoreTuning, GarbageCollectionStatus, GroupStats, MaintenanceMode,
+    MaintenanceType, Operation, StorageStatus, UPID,
 };
 
-use crate::backup_info::{BackupDir, BackupGroup, BackupGroupDeleteStats};
-use crate::chunk_store::ChunkStore;
+use crate::backup_info::{
+    BackupDir, BackupGroup, BackupGroupDeleteStats, BackupInfo, BackupSortField,
+};
+use crate::chunk_store::{ChunkStore, GcMarkedChunks};
 use crate::dynamic_index::{DynamicIndexReader, DynamicIndexWriter};
 use crate::fixed_index::{FixedIndexReader, FixedIndexWriter};
 use crate::hierarchy::{ListGroups, ListGroupsType, ListNamespaces, ListNamespacesRecursive};
 use crate::index::IndexFile;
-use crate::manifest::{archive_type, ArchiveType};
+use crate::manifest::{
+    archive_type, ArchiveType, BackupManifest, CLIENT_LOG_BLOB_NAME, MANIFEST_BLOB_NAME,
+    MANIFEST_LOCK_NAME,
+};
 use crate::task_tracking::{self, update_active_operations};
 use crate::DataBlob;
 
+/// Maximum number of seconds a new snapshot's backup time may lie in the future.
+pub const MAX_BACKUP_TIME_IN_FUTURE: i64 = 300;
+
 lazy_static! {
     static ref DATASTORE_MAP: Mutex<HashMap<String, Arc<DataStoreImpl>>> =
         Mutex::new(HashMap::new());
@@ -49,6 +61,129 @@ pub fn check_backup_owner(owner: &Authid, auth_id: &Authid) -> Result<(), Error>
     Ok(())
 }
 
+/// Result of a garbage collection dry-run, see [`DataStore::garbage_collect_dry_run`]
+#[derive(Clone, Debug, Default)]
+pub struct GCSummary {
+    /// Number of chunks which are not referenced by any index anymore
+    pub unreferenced_chunks: u64,
+    /// Bytes a garbage collection run would free
+    pub reclaimable_bytes: u64,
+    /// Number of protected snapshots on the datastore
+    pub protected_snapshots: usize,
+}
+
+/// Result of [`DataStore::capacity_alert`] if the datastore is running full.
+#[derive(Clone, Debug)]
+pub struct StorageAlert {
+    /// Used space of the underlying file system in percent
+    pub used_percent: f64,
+    /// Available space in bytes
+    pub free_bytes: u64,
+    /// Estimated number of days until the datastore is full, if it is growing
+    pub estimated_days_until_full: Option<f64>,
+}
+
+impl StorageAlert {
+    /// Returns an alert if the used space of `status` reached `threshold` percent.
+    ///
+    /// The time until full is only estimated for a positive `growth_per_day` (in bytes).
+    pub fn check(
+        status: &StorageStatus,
+        threshold: f64,
+        growth_per_day: Option<f64>,
+    ) -> Option<Self> {
+        let used_percent = status.usage_percent();
+        if used_percent < threshold {
+            return None;
+        }
+
+        let estimated_days_until_full = growth_per_day
+            .filter(|growth| *growth > 0.0)
+            .map(|growth| status.avail as f64 / growth);
+
+        Some(Self {
+            used_percent,
+            free_bytes: status.avail,
+            estimated_days_until_full,
+        })
+    }
+}
+
+/// Errors returned by [`DataStore`] methods which callers may want to handle.
+#[derive(Debug)]
+pub enum DatastoreError {
+    /// The requested namespace does not exist on the datastore.
+    NamespaceNotFound { store: String, ns: BackupNamespace },
+    /// The requested chunk does not exist on the datastore.
+    ChunkNotFound { store: String, digest: [u8; 32] },
+}
+
+impl std::error::Error for DatastoreError {}
+
+impl std::fmt::Display for DatastoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DatastoreError::NamespaceNotFound { store, ns } => {
+                write!(f, "namespace '{ns}' does not exist on datastore '{store}'")
+            }
+            DatastoreError::ChunkNotFound { store, digest } => {
+                let digest = hex::encode(digest);
+                write!(f, "chunk '{digest}' does not exist on datastore '{store}'")
+            }
+        }
+    }
+}
+
+/// Periodic progress reporting for the GC mark phase.
+struct GcMarkProgress {
+    interval: Option<Duration>,
+    last_report: Instant,
+    images_done: usize,
+    image_count: usize,
+    checked_chunks: u64,
+    marked_chunks: u64,
+}
+
+impl GcMarkProgress {
+    fn new(interval: Option<Duration>, image_count: usize) -> Self {
+        Self {
+            interval,
+            last_report: Instant::now(),
+            images_done: 0,
+            image_count,
+            checked_chunks: 0,
+            marked_chunks: 0,
+        }
+    }
+
+    /// Log progress if the interval elapsed. The total chunk count is extrapolated from the
+    /// average number of chunks per index file processed so far.
+    fn maybe_report(&mut self, worker: &dyn WorkerTaskContext) {
+        let interval = match self.interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        if self.last_report.elapsed() < interval {
+            return;
+        }
+        self.last_report = Instant::now();
+
+        let estimated_total = if self.images_done > 0 {
+            (self.checked_chunks * self.image_count as u64 / self.images_done as u64)
+                .max(self.checked_chunks)
+        } else {
+            self.checked_chunks
+        };
+        task_log!(
+            worker,
+            "GC progress: checked {} of ~{} chunks, marked {} as used",
+            self.checked_chunks,
+            estimated_total,
+            self.marked_chunks,
+        );
+    }
+}
+
 /// Datastore Management
 ///
 /// A Datastore can store severals backups, and provides the
@@ -139,10 +274,31 @@ impl DataStore {
         })
     }
 
+    /// Open the datastore `name` for `operation`.
+    ///
+    /// If `namespace` is set, it must exist on the datastore, otherwise a
+    /// [`DatastoreError::NamespaceNotFound`] error is returned.
     pub fn lookup_datastore(
         name: &str,
         operation: Option<Operation>,
+        namespace: Option<&BackupNamespace>,
     ) -> Result<Arc<DataStore>, Error> {
+        let datastore = Self::open_datastore(name, operation)?;
+
+        if let Some(ns) = namespace {
+            if !datastore.namespace_exists(ns) {
+                return Err(DatastoreError::NamespaceNotFound {
+                    store: name.to_string(),
+                    ns: ns.clone(),
+                }
+                .into());
+            }
+        }
+
+        Ok(datastore)
+    }
+
+    fn open_datastore(name: &str, operation: Option<Operation>) -> Result<Arc<DataStore>, Error> {
         // Avoid TOCTOU between checking maintenance mode and updating active operation counter, as
         // we use it to decide whether it is okay to delete the datastore.
         let config_lock = pbs_config::datastore::lock_config()?;
@@ -223,7 +379,7 @@ impl DataStore {
         {
             // the datastore drop handler does the checking if tasks are running and clears the
             // cache entry, so we just have to trigger it here
-            let _ = DataStore::lookup_datastore(name, Some(Operation::Lookup));
+            let _ = DataStore::lookup_datastore(name, Some(Operation::Lookup), None);
         }
 
         Ok(())
@@ -411,6 +567,69 @@ impl DataStore {
         Ok(())
     }
 
+    /// Returns the digests of all chunks referenced by a snapshot which are missing from the
+    /// chunk store, sorted and without duplicates.
+    ///
+    /// Useful to find out what has to be restored (e.g. from tape) to repair a snapshot that
+    /// failed verification.
+    pub fn scan_missing_chunks(&self, snapshot: &BackupDir) -> Result<Vec<[u8; 32]>, Error> {
+        let _guard = lock_dir_noblock_shared(
+            &snapshot.full_path(),
+            "snapshot",
+            "locked by another operation",
+        )?;
+        let (manifest, _) = snapshot.load_manifest()?;
+
+        let mut checked = HashSet::new();
+        let mut missing = Vec::new();
+
+        for info in manifest.files() {
+            match archive_type(info.filename())? {
+                ArchiveType::FixedIndex | ArchiveType::DynamicIndex => (),
+                ArchiveType::Blob => continue,
+            }
+            let mut path = snapshot.relative_path();
+            path.push(info.filename());
+            let index = self.open_index(&path)?;
+
+            for pos in 0..index.index_count() {
+                let digest = index.index_digest(pos).unwrap();
+                if !checked.insert(*digest) {
+                    continue;
+                }
+                let (chunk_path, digest_str) = self.chunk_path(digest);
+                match std::fs::metadata(chunk_path) {
+                    Ok(_) => (),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => missing.push(*digest),
+                    Err(err) => bail!("unable to stat chunk '{digest_str}' - {err}"),
+                }
+            }
+        }
+
+        missing.sort_unstable();
+
+        Ok(missing)
+    }
+
+    /// Checks if the used space of the datastore's file system reached `threshold` percent.
+    ///
+    /// `growth_per_day` is the average increase of used space in bytes per day, e.g. derived
+    /// from the RRD statistics, and is used to estimate when the datastore will be full.
+    pub fn capacity_alert(
+        &self,
+        threshold: f64,
+        growth_per_day: Option<f64>,
+    ) -> Result<Option<StorageAlert>, Error> {
+        let info = proxmox_sys::fs::fs_info(&self.base_path())?;
+        let status = StorageStatus {
+            total: info.total,
+            used: info.used,
+            avail: info.available,
+        };
+
+        Ok(StorageAlert::check(&status, threshold, growth_per_day))
+    }
+
     pub fn name(&self) -> &str {
         self.inner.chunk_store.name()
     }
@@ -419,6 +638,49 @@ impl DataStore {
         self.inner.chunk_store.base_path()
     }
 
+    /// Returns the directory holding the manifest lock files of this datastore.
+    pub(crate) fn manifest_lock_base_path(&self) -> PathBuf {
+        PathBuf::from(format!("/run/proxmox-backup/locks/{}", self.name()))
+    }
+
+    /// Removes orphaned manifest lock files of all snapshots of this datastore.
+    ///
+    /// See [BackupDir::cleanup_lock_files]. Returns the number of removed lock files.
+    pub fn cleanup_manifest_lock_files(self: &Arc<Self>) -> Result<usize, Error> {
+        let base = self.manifest_lock_base_path();
+        if !base.exists() {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for entry in walkdir::WalkDir::new(&base) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let snapshot = match path
+                .strip_prefix(&base)
+                .ok()
+                .and_then(|path| path.to_str())
+                .and_then(|path| path.strip_suffix(MANIFEST_LOCK_NAME))
+            {
+                Some(snapshot) => snapshot,
+                None => continue,
+            };
+
+            let result = pbs_api_types::parse_ns_and_snapshot(snapshot)
+                .and_then(|(ns, dir)| self.backup_dir(ns, dir))
+                .and_then(|dir| dir.cleanup_lock_files());
+            match result {
+                Ok(count) => removed += count,
+                Err(err) => log::warn!("could not clean up manifest lock {path:?} - {err}"),
+            }
+        }
+
+        Ok(removed)
+    }
+
     /// Returns the absolute path for a backup namespace on this datastore
     pub fn namespace_path(&self, ns: &BackupNamespace) -> PathBuf {
         let mut path = self.base_path();
@@ -459,6 +721,20 @@ impl DataStore {
         full_path
     }
 
+    /// Options to create directories inside the datastore with.
+    ///
+    /// Directories are owned by the backup user and group, so that they stay accessible for the
+    /// proxy even if created by a process running as root.
+    pub fn backup_dir_options() -> Result<CreateOptions, Error> {
+        let backup_user = pbs_config::backup_user()?;
+        let mode = nix::sys::stat::Mode::from_bits_truncate(0o0755);
+
+        Ok(CreateOptions::new()
+            .perm(mode)
+            .owner(backup_user.uid)
+            .group(backup_user.gid))
+    }
+
     /// Create a backup namespace.
     pub fn create_namespace(
         self: &Arc<Self>,
@@ -475,7 +751,8 @@ impl DataStore {
         let mut ns_full_path = self.base_path();
         ns_full_path.push(ns.path());
 
-        std::fs::create_dir_all(ns_full_path)?;
+        let options = Self::backup_dir_options()?;
+        create_path(ns_full_path, Some(options.clone()), Some(options))?;
 
         Ok(ns)
     }
@@ -588,18 +865,20 @@ impl DataStore {
         ns: &BackupNamespace,
         backup_group: &pbs_api_types::BackupGroup,
     ) -> Result<BackupGroupDeleteStats, Error> {
-        let backup_group = self.backup_group(ns.clone(), backup_group.clone());
+        let backup_group = self.backup_group(ns.clone(), backup_group.clone())?;
 
         backup_group.destroy()
     }
 
     /// Remove a backup directory including all content
+    ///
+    /// Returns the total size of the removed files, see [BackupDir::destroy].
     pub fn remove_backup_dir(
         self: &Arc<Self>,
         ns: &BackupNamespace,
         backup_dir: &pbs_api_types::BackupDir,
         force: bool,
-    ) -> Result<(), Error> {
+    ) -> Result<u64, Error> {
         let backup_dir = self.backup_dir(ns.clone(), backup_dir.clone())?;
 
         backup_dir.destroy(force)
@@ -613,12 +892,14 @@ impl DataStore {
         ns: &BackupNamespace,
         backup_group: &pbs_api_types::BackupGroup,
     ) -> Result<Option<i64>, Error> {
-        let backup_group = self.backup_group(ns.clone(), backup_group.clone());
+        let backup_group = self.backup_group(ns.clone(), backup_group.clone())?;
 
         let group_path = backup_group.full_group_path();
 
         if group_path.exists() {
-            backup_group.last_successful_backup()
+            Ok(backup_group
+                .last_successful_backup()?
+                .map(|info| info.backup_dir.backup_time()))
         } else {
             Ok(None)
         }
@@ -664,6 +945,7 @@ impl DataStore {
         auth_id: &Authid,
         force: bool,
     ) -> Result<(), Error> {
+        backup_group.validate()?;
         let path = self.owner_path(ns, backup_group);
 
         let mut open_options = std::fs::OpenOptions::new();
@@ -698,6 +980,8 @@ impl DataStore {
         backup_group: &pbs_api_types::BackupGroup,
         auth_id: &Authid,
     ) -> Result<(Authid, DirLockGuard), Error> {
+        backup_group.validate()?;
+
         // create intermediate path first:
         let mut full_path = self.base_path();
         for ns in ns.components() {
@@ -705,27 +989,28 @@ impl DataStore {
             full_path.push(ns);
         }
         full_path.push(backup_group.ty.as_str());
-        std::fs::create_dir_all(&full_path)?;
+        let options = Self::backup_dir_options()?;
+        create_path(&full_path, Some(options.clone()), Some(options.clone()))?;
 
         full_path.push(&backup_group.id);
 
         // create the last component now
-        match std::fs::create_dir(&full_path) {
-            Ok(_) => {
+        match create_dir(&full_path, options) {
+            Ok(()) => {
                 let guard = lock_dir_noblock(
                     &full_path,
                     "backup group",
-                    "another backup is already running",
+                    "another backup or a prune is already running",
                 )?;
                 self.set_owner(ns, backup_group, auth_id, false)?;
                 let owner = self.get_owner(ns, backup_group)?; // just to be sure
                 Ok((owner, guard))
             }
-            Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            Err(nix::errno::Errno::EEXIST) => {
                 let guard = lock_dir_noblock(
                     &full_path,
                     "backup group",
-                    "another backup is already running",
+                    "another backup or a prune is already running",
                 )?;
                 let owner = self.get_owner(ns, backup_group)?; // just to be sure
                 Ok((owner, guard))
@@ -737,11 +1022,26 @@ impl DataStore {
     /// Creates a new backup snapshot inside a BackupGroup
     ///
     /// The BackupGroup directory needs to exist.
+    ///
+    /// Unless `allow_future_time` is set, backup times more than
+    /// [MAX_BACKUP_TIME_IN_FUTURE] seconds ahead of the current time are rejected. Callers
+    /// importing already existing snapshots (sync, tape restore) should allow them.
     pub fn create_locked_backup_dir(
         &self,
         ns: &BackupNamespace,
         backup_dir: &pbs_api_types::BackupDir,
+        allow_future_time: bool,
     ) -> Result<(PathBuf, bool, DirLockGuard), Error> {
+        if !allow_future_time {
+            let now = proxmox_time::epoch_i64();
+            if backup_dir.time > now + MAX_BACKUP_TIME_IN_FUTURE {
+                bail!(
+                    "backup time of {backup_dir} is more than {MAX_BACKUP_TIME_IN_FUTURE} seconds \
+                    in the future"
+                );
+            }
+        }
+
         let full_path = self.snapshot_path(ns, backup_dir);
         let relative_path = full_path.strip_prefix(self.base_path()).map_err(|err| {
             format_err!(
@@ -757,11 +1057,9 @@ impl DataStore {
             )
         };
 
-        match std::fs::create_dir(&full_path) {
-            Ok(_) => Ok((relative_path.to_owned(), true, lock()?)),
-            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {
-                Ok((relative_path.to_owned(), false, lock()?))
-            }
+        match create_dir(&full_path, Self::backup_dir_options()?) {
+            Ok(()) => Ok((relative_path.to_owned(), true, lock()?)),
+            Err(nix::errno::Errno::EEXIST) => Ok((relative_path.to_owned(), false, lock()?)),
             Err(e) => Err(e.into()),
         }
     }
@@ -832,6 +1130,18 @@ impl DataStore {
         }))
     }
 
+    /// Get a in-memory vector of all backup namespaces of a datastore, including the root.
+    ///
+    /// The namespaces are sorted by depth first (root first, deepest last), then alphabetically.
+    pub fn list_namespaces(self: &Arc<DataStore>) -> Result<Vec<BackupNamespace>, Error> {
+        let mut list = ListNamespacesRecursive::new(Arc::clone(self), BackupNamespace::root())?
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        list.sort_unstable_by(|a, b| a.depth().cmp(&b.depth()).then_with(|| a.cmp(b)));
+
+        Ok(list)
+    }
+
     /// Get a streaming iter over top-level backup groups of a datatstore of a particular type.
     ///
     /// The iterated item is still a Result that can contain errors from rather unexptected FS or
@@ -879,6 +1189,40 @@ impl DataStore {
         Ok(self.iter_backup_groups(ns)?.ok())
     }
 
+    /// Get a streaming iter over all unfinished snapshots (without a manifest) of a datastore
+    ///
+    /// Namespaces and groups are traversed recursively, errors while listing those are logged
+    /// and skipped, like in `recursive_iter_backup_ns_ok` and `iter_backup_groups_ok`.
+    pub fn iter_unfinished_snapshots(
+        self: &Arc<DataStore>,
+    ) -> Result<impl Iterator<Item = Result<BackupDir, Error>> + 'static, Error> {
+        let this = Arc::clone(self);
+        Ok(self
+            .recursive_iter_backup_ns_ok(BackupNamespace::root(), None)?
+            .flat_map(move |ns| -> Box<dyn Iterator<Item = BackupGroup>> {
+                match this.iter_backup_groups_ok(ns) {
+                    Ok(groups) => Box::new(groups),
+                    Err(err) => {
+                        log::error!("list groups error on datastore {} - {}", this.name(), err);
+                        Box::new(std::iter::empty())
+                    }
+                }
+            })
+            .flat_map(
+                |group| -> Box<dyn Iterator<Item = Result<BackupDir, Error>>> {
+                    match group.iter_snapshots() {
+                        Ok(snapshots) => Box::new(snapshots),
+                        Err(err) => Box::new(std::iter::once(Err(err))),
+                    }
+                },
+            )
+            .filter_map(|snapshot| match snapshot.and_then(BackupInfo::new) {
+                Ok(info) if info.is_finished() => None,
+                Ok(info) => Some(Ok(info.backup_dir)),
+                Err(err) => Some(Err(err)),
+            }))
+    }
+
     /// Get a in-memory vector for all top-level backup groups of a datatstore
     ///
     /// NOTE: using the iterator directly is most often more efficient w.r.t. memory usage
@@ -948,12 +1292,32 @@ impl DataStore {
         Ok(list)
     }
 
+    /// Get a streaming iter over all files of the datastore, in filesystem order
+    ///
+    /// This includes the chunk files as well as all files in the snapshot directories and the
+    /// metadata files of groups (owner, notes). Yielded paths are absolute. The (root-only) ext4
+    /// `lost+found` directory is skipped.
+    pub fn list_all_backup_files(&self) -> impl Iterator<Item = Result<PathBuf, Error>> {
+        walkdir::WalkDir::new(self.base_path())
+            .into_iter()
+            .filter_entry(|entry| !(entry.depth() == 1 && entry.file_name() == "lost+found"))
+            .filter_map(|entry| match entry {
+                Ok(entry) if entry.file_type().is_file() => Some(Ok(entry.into_path())),
+                Ok(_) => None,
+                Err(err) => Some(Err(err.into())),
+            })
+    }
+
     // mark chunks  used by ``index`` as used
+    //
+    // With `dry_run` set, the chunks are only recorded there instead of touching them.
     fn index_mark_used_chunks<I: IndexFile>(
         &self,
         index: I,
         file_name: &Path, // only used for error reporting
         status: &mut GarbageCollectionStatus,
+        progress: &mut GcMarkProgress,
+        mut dry_run: Option<&mut GcMarkedChunks>,
         worker: &dyn WorkerTaskContext,
     ) -> Result<(), Error> {
         status.index_file_count += 1;
@@ -963,12 +1327,31 @@ impl DataStore {
             worker.check_abort()?;
             worker.fail_on_shutdown()?;
             let digest = index.index_digest(pos).unwrap();
-            if !self.inner.chunk_store.cond_touch_chunk(digest, false)? {
+            progress.checked_chunks += 1;
+            progress.maybe_report(worker);
+            let exists = match dry_run.as_deref_mut() {
+                Some(marked) => {
+                    let exists = self.chunk_path(digest).0.exists();
+                    if exists {
+                        marked.chunks.insert(*digest);
+                    } else {
+                        marked.missing.insert(*digest);
+                    }
+                    exists
+                }
+                None => self.inner.chunk_store.cond_touch_chunk(digest, false)?,
+            };
+            if exists {
+                progress.marked_chunks += 1;
+            } else {
                 let hex = hex::encode(digest);
                 task_warn!(
                     worker,
                     "warning: unable to access non-existent chunk {hex}, required by {file_name:?}"
                 );
+                if dry_run.is_some() {
+                    continue;
+                }
 
                 // touch any corresponding .bad files to keep them around, meaning if a chunk is
                 // rewritten correctly they will be removed automatically, as well as if no index
@@ -988,11 +1371,15 @@ impl DataStore {
     fn mark_used_chunks(
         &self,
         status: &mut GarbageCollectionStatus,
+        progress_interval: Option<Duration>,
+        mut dry_run: Option<&mut GcMarkedChunks>,
         worker: &dyn WorkerTaskContext,
     ) -> Result<(), Error> {
         let image_list = self.list_images()?;
         let image_count = image_list.len();
 
+        let mut progress = GcMarkProgress::new(progress_interval, image_count);
+
         let mut last_percentage: usize = 0;
 
         let mut strange_paths_count: u64 = 0;
@@ -1000,6 +1387,7 @@ impl DataStore {
         for (i, img) in image_list.into_iter().enumerate() {
             worker.check_abort()?;
             worker.fail_on_shutdown()?;
+            progress.images_done = i;
 
             if let Some(backup_dir_path) = img.parent() {
                 let backup_dir_path = backup_dir_path.strip_prefix(self.base_path())?;
@@ -1017,12 +1405,26 @@ impl DataStore {
                             let index = FixedIndexReader::new(file).map_err(|e| {
                                 format_err!("can't read index '{}' - {}", img.to_string_lossy(), e)
                             })?;
-                            self.index_mark_used_chunks(index, &img, status, worker)?;
+                            self.index_mark_used_chunks(
+                                index,
+                                &img,
+                                status,
+                                &mut progress,
+                                dry_run.as_deref_mut(),
+                                worker,
+                            )?;
                         } else if archive_type == ArchiveType::DynamicIndex {
                             let index = DynamicIndexReader::new(file).map_err(|e| {
                                 format_err!("can't read index '{}' - {}", img.to_string_lossy(), e)
                             })?;
-                            self.index_mark_used_chunks(index, &img, status, worker)?;
+                            self.index_mark_used_chunks(
+                                index,
+                                &img,
+                                status,
+                                &mut progress,
+                                dry_run.as_deref_mut(),
+                                worker,
+                            )?;
                         }
                     }
                 }
@@ -1062,10 +1464,15 @@ impl DataStore {
         self.inner.gc_mutex.try_lock().is_err()
     }
 
+    /// Run garbage collection on this datastore.
+    ///
+    /// If `progress_interval` is set, the mark phase logs the number of checked and marked
+    /// chunks each time the interval elapses.
     pub fn garbage_collection(
         &self,
         worker: &dyn WorkerTaskContext,
         upid: &UPID,
+        progress_interval: Option<Duration>,
     ) -> Result<(), Error> {
         if let Ok(ref mut _mutex) = self.inner.gc_mutex.try_lock() {
             // avoids that we run GC if an old daemon process has still a
@@ -1087,7 +1494,7 @@ impl DataStore {
 
             task_log!(worker, "Start GC phase1 (mark used chunks)");
 
-            self.mark_used_chunks(&mut gc_status, worker)?;
+            self.mark_used_chunks(&mut gc_status, progress_interval, None, worker)?;
 
             task_log!(worker, "Start GC phase2 (sweep unused chunks)");
             self.inner.chunk_store.sweep_unused_chunks(
@@ -1095,6 +1502,7 @@ impl DataStore {
                 phase1_start_time,
                 &mut gc_status,
                 worker,
+                None,
             )?;
 
             task_log!(
@@ -1176,6 +1584,104 @@ impl DataStore {
         Ok(())
     }
 
+    /// Preview what a garbage collection run would free.
+    ///
+    /// This takes the same locks as [`garbage_collection`](Self::garbage_collection), but the
+    /// mark phase only records the used chunks instead of updating their atime and the sweep
+    /// phase does not remove anything, so the datastore is not modified.
+    pub fn garbage_collect_dry_run(
+        self: &Arc<Self>,
+        worker: &dyn WorkerTaskContext,
+    ) -> Result<GCSummary, Error> {
+        let _mutex = match self.inner.gc_mutex.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => bail!("Start GC dry-run failed - (already running/locked)"),
+        };
+        let _exclusive_lock = self.inner.chunk_store.try_exclusive_lock()?;
+
+        let phase1_start_time = proxmox_time::epoch_i64();
+        let oldest_writer = self
+            .inner
+            .chunk_store
+            .oldest_writer()
+            .unwrap_or(phase1_start_time);
+
+        let mut gc_status = GarbageCollectionStatus::default();
+        let mut marked = GcMarkedChunks::default();
+
+        task_log!(worker, "Start GC dry-run phase1 (mark used chunks)");
+
+        self.mark_used_chunks(&mut gc_status, None, Some(&mut marked), worker)?;
+
+        task_log!(worker, "Start GC dry-run phase2 (count unused chunks)");
+        self.inner.chunk_store.sweep_unused_chunks(
+            oldest_writer,
+            phase1_start_time,
+            &mut gc_status,
+            worker,
+            Some(&marked),
+        )?;
+
+        let mut protected_snapshots = 0;
+        for ns in self.recursive_iter_backup_ns_ok(BackupNamespace::root(), None)? {
+            for group in self.iter_backup_groups_ok(ns)? {
+                for snapshot in group.iter_snapshots()? {
+                    if snapshot?.is_protected() {
+                        protected_snapshots += 1;
+                    }
+                }
+            }
+        }
+
+        let summary = GCSummary {
+            unreferenced_chunks: (gc_status.removed_chunks + gc_status.removed_bad) as u64,
+            reclaimable_bytes: gc_status.removed_bytes,
+            protected_snapshots,
+        };
+
+        task_log!(
+            worker,
+            "Reclaimable garbage: {} (in {} chunks)",
+            HumanByte::from(summary.reclaimable_bytes),
+            summary.unreferenced_chunks,
+        );
+        task_log!(
+            worker,
+            "Protected snapshots: {}",
+            summary.protected_snapshots
+        );
+
+        Ok(summary)
+    }
+
+    /// Compute the disk space used by the chunk store.
+    ///
+    /// Sums up the allocated blocks of all chunk files (including `.bad` ones), so sparse files
+    /// are accounted for correctly. Chunk data is not read.
+    pub fn compute_chunk_store_size(&self) -> Result<u64, Error> {
+        use nix::sys::stat::fstatat;
+
+        let mut size = 0;
+
+        for (entry, _percentage, _bad) in self.inner.chunk_store.get_chunk_iterator()? {
+            let entry = entry.map_err(|err| {
+                format_err!("chunk iterator on store '{}' failed - {}", self.name(), err)
+            })?;
+
+            match fstatat(
+                entry.parent_fd(),
+                entry.file_name(),
+                nix::fcntl::AtFlags::AT_SYMLINK_NOFOLLOW,
+            ) {
+                Ok(stat) => size += stat.st_blocks as u64 * 512,
+                Err(nix::errno::Errno::ENOENT) => (), // ignore vanished chunks
+                Err(err) => bail!("unable to stat chunk {:?} - {}", entry.file_name(), err),
+            }
+        }
+
+        Ok(size)
+    }
+
     pub fn try_shared_chunk_store_lock(&self) -> Result<ProcessLockSharedGuard, Error> {
         self.inner.chunk_store.try_shared_lock()
     }
@@ -1194,11 +1700,56 @@ impl DataStore {
         self.inner.chunk_store.insert_chunk(chunk, digest)
     }
 
+    /// Import chunks from a tar archive into the chunk store.
+    ///
+    /// Every regular file in the archive must be named after the hex encoded SHA-256 digest of
+    /// the chunk it contains. The CRC of each chunk is checked, and unencrypted chunks are also
+    /// verified against their digest. Returns the number of chunks which were not yet present.
+    pub fn import_chunks_from_tar<R: std::io::Read>(&self, reader: R) -> Result<u64, Error> {
+        let mut archive = tar::Archive::new(reader);
+        let mut imported = 0;
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let path = entry.path()?.into_owned();
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| format_err!("invalid chunk file name {:?}", path))?;
+            let mut digest = [0u8; 32];
+            if name.len() != 64 || hex::decode_to_slice(name, &mut digest).is_err() {
+                bail!("invalid chunk file name {:?}", path);
+            }
+
+            let chunk = DataBlob::load_from_reader(&mut entry)
+                .map_err(|err| format_err!("unable to load chunk {:?} - {}", path, err))?;
+            if !chunk.is_encrypted() {
+                chunk
+                    .decode(None, Some(&digest))
+                    .map_err(|err| format_err!("chunk {:?} failed verification - {}", path, err))?;
+            }
+
+            let (is_duplicate, _) = self.insert_chunk(&chunk, &digest)?;
+            if !is_duplicate {
+                imported += 1;
+            }
+        }
+
+        Ok(imported)
+    }
+
     pub fn stat_chunk(&self, digest: &[u8; 32]) -> Result<std::fs::Metadata, Error> {
         let (chunk_path, _digest_str) = self.inner.chunk_store.chunk_path(digest);
         std::fs::metadata(chunk_path).map_err(Error::from)
     }
 
+    /// Open and load a chunk.
+    ///
+    /// If the chunk does not exist, a [`DatastoreError::ChunkNotFound`] error is returned.
     pub fn load_chunk(&self, digest: &[u8; 32]) -> Result<DataBlob, Error> {
         let (chunk_path, digest_str) = self.inner.chunk_store.chunk_path(digest);
 
@@ -1206,13 +1757,20 @@ impl DataStore {
             let mut file = std::fs::File::open(&chunk_path)?;
             DataBlob::load_from_reader(&mut file)
         })
-        .map_err(|err| {
-            format_err!(
+        .map_err(|err| match err.downcast_ref::<io::Error>() {
+            Some(io_err) if io_err.kind() == io::ErrorKind::NotFound => {
+                DatastoreError::ChunkNotFound {
+                    store: self.name().to_string(),
+                    digest: *digest,
+                }
+                .into()
+            }
+            _ => format_err!(
                 "store '{}', unable to load chunk '{}' - {}",
                 self.name(),
                 digest_str,
                 err,
-            )
+            ),
         })
     }
 
@@ -1240,6 +1798,66 @@ impl DataStore {
         Ok(())
     }
 
+    /// Reconstruct a missing manifest of a snapshot from the files present in its directory.
+    ///
+    /// Checksums and sizes are computed from the index and blob files, the crypt mode is derived
+    /// from the first chunk of each index. A reconstructed manifest is never signed, so
+    /// `sign-only` snapshots will show up as unencrypted.
+    pub fn repair_missing_manifest(&self, backup_dir: &BackupDir) -> Result<(), Error> {
+        let full_path = backup_dir.full_path();
+
+        let _guard = lock_dir_noblock(&full_path, "snapshot", "possibly running or in use")?;
+        let _manifest_guard = backup_dir.lock_manifest()?;
+
+        let mut manifest_path = full_path.clone();
+        manifest_path.push(MANIFEST_BLOB_NAME);
+        if manifest_path.exists() {
+            bail!("snapshot {} already has a manifest", backup_dir.dir());
+        }
+
+        let mut manifest = BackupManifest::new(backup_dir.into());
+
+        let info = BackupInfo::new(backup_dir.clone())?;
+        for filename in info.files {
+            if filename == CLIENT_LOG_BLOB_NAME {
+                continue;
+            }
+
+            let mut path = full_path.clone();
+            path.push(&filename);
+
+            let (csum, size, crypt_mode) = match archive_type(&filename)? {
+                ArchiveType::FixedIndex | ArchiveType::DynamicIndex => {
+                    let index = self.open_index(&path)?;
+                    let (csum, size) = index.compute_csum();
+                    let crypt_mode = match index.index_digest(0) {
+                        Some(digest) => self.load_chunk(digest)?.crypt_mode()?,
+                        None => CryptMode::None,
+                    };
+                    (csum, size, crypt_mode)
+                }
+                ArchiveType::Blob => {
+                    let blob = backup_dir.load_blob(&filename)?;
+                    let csum = openssl::sha::sha256(blob.raw_data());
+                    (csum, blob.raw_size(), blob.crypt_mode()?)
+                }
+            };
+
+            manifest.add_file(filename, size, csum, crypt_mode)?;
+        }
+
+        if manifest.files().next().is_none() {
+            bail!("snapshot {} does not contain any files", backup_dir.dir());
+        }
+
+        let manifest = manifest.to_string(None)?;
+        let blob = DataBlob::encode(manifest.as_bytes(), None, true)?;
+
+        replace_file(&manifest_path, blob.raw_data(), CreateOptions::new(), false)?;
+
+        Ok(())
+    }
+
     pub fn verify_new(&self) -> bool {
         self.inner.verify_new
     }
@@ -1297,17 +1915,84 @@ impl DataStore {
         self: &Arc<Self>,
         ns: BackupNamespace,
         group: pbs_api_types::BackupGroup,
-    ) -> BackupGroup {
+    ) -> Result<BackupGroup, Error> {
         BackupGroup::new(Arc::clone(self), ns, group)
     }
 
+    /// Aggregate snapshot count, size and time range of a backup group.
+    pub fn stats_for_group(&self, group: &BackupGroup) -> Result<GroupStats, Error> {
+        let mut stats = GroupStats::default();
+
+        for info in group.list_backups()? {
+            let backup_time = info.backup_dir.backup_time();
+            if stats.snapshot_count == 0 {
+                stats.oldest_snapshot = backup_time;
+                stats.newest_snapshot = backup_time;
+            } else {
+                stats.oldest_snapshot = stats.oldest_snapshot.min(backup_time);
+                stats.newest_snapshot = stats.newest_snapshot.max(backup_time);
+            }
+            stats.snapshot_count += 1;
+            stats.total_size += info.total_file_size()?;
+            stats.has_unfinished |= !info.is_finished();
+        }
+
+        Ok(stats)
+    }
+
+    /// Find the snapshot of a group which was current at `target_time` (epoch).
+    ///
+    /// This is the most recent finished snapshot with a backup time at or before `target_time`,
+    /// or `None` if the group has no such snapshot.
+    pub fn snapshot_for_pitr(
+        &self,
+        group: &BackupGroup,
+        target_time: i64,
+    ) -> Result<Option<BackupDir>, Error> {
+        let mut list: Vec<BackupInfo> = group
+            .list_backups()?
+            .into_iter()
+            .filter(BackupInfo::is_finished)
+            .collect();
+        BackupInfo::sort_list(&mut list, BackupSortField::Time, true);
+
+        let idx = list.partition_point(|info| info.backup_dir.backup_time() <= target_time);
+        if idx == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(list.swap_remove(idx - 1).backup_dir))
+    }
+
+    /// Look up the snapshot of a group with the given backup time (epoch).
+    ///
+    /// Only the expected snapshot directory is checked, without scanning the whole group.
+    /// Returns `None` if there is no such snapshot.
+    pub fn find_snapshot(
+        &self,
+        group: &BackupGroup,
+        time: i64,
+    ) -> Result<Option<BackupDir>, Error> {
+        let snapshot = group.backup_dir(time)?;
+
+        match std::fs::metadata(snapshot.full_path()) {
+            Ok(metadata) if metadata.is_dir() => Ok(Some(snapshot)),
+            Ok(_) => Ok(None),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => bail!(
+                "unable to stat snapshot {:?} - {err}",
+                snapshot.relative_path()
+            ),
+        }
+    }
+
     /// Open a backup group from this datastore.
     pub fn backup_group_from_parts<T>(
         self: &Arc<Self>,
         ns: BackupNamespace,
         ty: BackupType,
         id: T,
-    ) -> BackupGroup
+    ) -> Result<BackupGroup, Error>
     where
         T: Into<String>,
     {
@@ -1329,7 +2014,7 @@ impl DataStore {
         ns: BackupNamespace,
         dir: pbs_api_types::BackupDir,
     ) -> Result<BackupDir, Error> {
-        BackupDir::with_group(self.backup_group(ns, dir.group), dir.time)
+        BackupDir::with_group(self.backup_group(ns, dir.group)?, dir.time)
     }
 
     /// Open a snapshot (backup directory) from this datastore.
@@ -1487,3 +2172,35 @@ impl DataStore {
         Ok(())
     }
 }
+
+#[test]
+fn test_storage_alert() {
+    let status = |used, avail| StorageStatus {
+        total: used + avail,
+        used,
+        avail,
+    };
+
+    // below the threshold
+    assert!(StorageAlert::check(&status(2, 1), 75.0, Some(1.0)).is_none());
+
+    // the threshold itself already alerts
+    let alert = StorageAlert::check(&status(3, 1), 75.0, Some(0.5)).unwrap();
+    assert_eq!(alert.used_percent, 75.0);
+    assert_eq!(alert.free_bytes, 1);
+    assert_eq!(alert.estimated_days_until_full, Some(2.0));
+
+    // no estimate if the datastore does not grow
+    for growth in [None, Some(0.0), Some(-100.0)] {
+        let alert = StorageAlert::check(&status(7, 1), 75.0, growth).unwrap();
+        assert_eq!(alert.estimated_days_until_full, None);
+    }
+
+    // unknown total size never alerts
+    let unknown = StorageStatus {
+        total: 0,
+        used: 0,
+        avail: 0,
+    };
+    assert!(StorageAlert::check(&unknown, 75.0, Some(1.0)).is_none());
+}
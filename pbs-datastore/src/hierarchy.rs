@@ -127,11 +127,11 @@ impl Iterator for ListGroupsType {
 
             if let Ok(name) = entry.file_name().to_str() {
                 if BACKUP_ID_REGEX.is_match(name) {
-                    return Some(Ok(BackupGroup::new(
+                    return Some(BackupGroup::new(
                         Arc::clone(&self.store),
                         self.ns.clone(),
                         (self.ty, name.to_owned()).into(),
-                    )));
+                    ));
                 }
             }
         }
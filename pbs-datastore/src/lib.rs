@@ -192,7 +192,7 @@ pub mod task_tracking;
 pub mod dynamic_index;
 pub mod fixed_index;
 
-pub use backup_info::{BackupDir, BackupGroup, BackupInfo};
+pub use backup_info::{BackupDir, BackupGroup, BackupInfo, BackupSortField};
 pub use checksum_reader::ChecksumReader;
 pub use checksum_writer::ChecksumWriter;
 pub use chunk_store::ChunkStore;
@@ -206,7 +206,10 @@ pub use manifest::BackupManifest;
 pub use store_progress::StoreProgress;
 
 mod datastore;
-pub use datastore::{check_backup_owner, DataStore};
+pub use datastore::{
+    check_backup_owner, DataStore, DatastoreError, GCSummary, StorageAlert,
+    MAX_BACKUP_TIME_IN_FUTURE,
+};
 
 mod hierarchy;
 pub use hierarchy::{
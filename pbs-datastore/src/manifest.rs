@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::path::Path;
 
 use anyhow::{bail, format_err, Error};
@@ -8,6 +9,8 @@ use serde_json::{json, Value};
 use pbs_api_types::{BackupType, CryptMode, Fingerprint};
 use pbs_tools::crypt_config::CryptConfig;
 
+use crate::BackupDir;
+
 pub const MANIFEST_BLOB_NAME: &str = "index.json.blob";
 pub const MANIFEST_LOCK_NAME: &str = ".index.json.lck";
 pub const CLIENT_LOG_BLOB_NAME: &str = "client.log.blob";
@@ -23,15 +26,35 @@ fn empty_value() -> Value {
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct FileInfo {
-    pub filename: String,
+    filename: String,
     #[serde(default = "crypt_mode_none")] // to be compatible with < 0.8.0 backups
-    pub crypt_mode: CryptMode,
-    pub size: u64,
+    crypt_mode: CryptMode,
+    size: u64,
     #[serde(with = "hex::serde")]
-    pub csum: [u8; 32],
+    csum: [u8; 32],
 }
 
 impl FileInfo {
+    /// Name of the archive file
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Crypt mode the file was written with
+    pub fn crypt_mode(&self) -> CryptMode {
+        self.crypt_mode
+    }
+
+    /// Size of the file as recorded in the manifest
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// SHA-256 checksum of the file as recorded in the manifest
+    pub fn csum(&self) -> &[u8; 32] {
+        &self.csum
+    }
+
     /// Return expected CryptMode of referenced chunks
     ///
     /// Encrypted Indices should only reference encrypted chunks, while signed or plain indices
@@ -44,6 +67,29 @@ impl FileInfo {
     }
 }
 
+/// Result of comparing a file against its manifest entry
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub enum VerifyFileResult {
+    Ok,
+    SizeMismatch { expected: u64, actual: u64 },
+    ChecksumMismatch { expected: String, actual: String },
+}
+
+impl std::fmt::Display for VerifyFileResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VerifyFileResult::Ok => write!(f, "ok"),
+            VerifyFileResult::SizeMismatch { expected, actual } => {
+                write!(f, "wrong size ({} != {})", expected, actual)
+            }
+            VerifyFileResult::ChecksumMismatch { expected, actual } => {
+                write!(f, "wrong checksum ({} != {})", expected, actual)
+            }
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct BackupManifest {
@@ -110,8 +156,8 @@ impl BackupManifest {
         Ok(())
     }
 
-    pub fn files(&self) -> &[FileInfo] {
-        &self.files[..]
+    pub fn files(&self) -> impl Iterator<Item = &FileInfo> {
+        self.files.iter()
     }
 
     pub fn lookup_file_info(&self, name: &str) -> Result<&FileInfo, Error> {
@@ -123,18 +169,77 @@ impl BackupManifest {
         }
     }
 
-    pub fn verify_file(&self, name: &str, csum: &[u8; 32], size: u64) -> Result<(), Error> {
+    /// Estimate how much data has to be read from the datastore to restore all files.
+    ///
+    /// Chunks referenced multiple times (within or across index files) are only counted once,
+    /// blobs are counted with their full size. The estimate is capped at the summed up size of
+    /// all files.
+    pub fn estimated_restore_size(&self, snapshot: &BackupDir) -> Result<u64, Error> {
+        let datastore = snapshot.datastore();
+        let mut total_file_size = 0;
+        let mut estimate = 0;
+        let mut seen = HashSet::new();
+
+        for info in self.files() {
+            total_file_size += info.size;
+            match archive_type(&info.filename)? {
+                ArchiveType::FixedIndex | ArchiveType::DynamicIndex => (),
+                ArchiveType::Blob => {
+                    estimate += info.size;
+                    continue;
+                }
+            }
+
+            let mut path = snapshot.relative_path();
+            path.push(&info.filename);
+            let index = datastore.open_index(&path)?;
+
+            for pos in 0..index.index_count() {
+                let chunk_info = index.chunk_info(pos).unwrap();
+                if seen.insert(chunk_info.digest) {
+                    estimate += chunk_info.size();
+                }
+            }
+        }
+
+        Ok(estimate.min(total_file_size))
+    }
+
+    /// Compare size and checksum of file `name` with the values stored in the manifest.
+    ///
+    /// Only fails if the manifest does not contain the file, mismatches are reported via the
+    /// returned [`VerifyFileResult`].
+    pub fn verify_file(
+        &self,
+        name: &str,
+        csum: &[u8; 32],
+        size: u64,
+    ) -> Result<VerifyFileResult, Error> {
         let info = self.lookup_file_info(name)?;
 
         if size != info.size {
-            bail!("wrong size for file '{}' ({} != {})", name, info.size, size);
+            return Ok(VerifyFileResult::SizeMismatch {
+                expected: info.size,
+                actual: size,
+            });
         }
 
         if csum != &info.csum {
-            bail!("wrong checksum for file '{}'", name);
+            return Ok(VerifyFileResult::ChecksumMismatch {
+                expected: hex::encode(info.csum),
+                actual: hex::encode(csum),
+            });
         }
 
-        Ok(())
+        Ok(VerifyFileResult::Ok)
+    }
+
+    /// Like [`verify_file`](Self::verify_file), but treats any mismatch as error.
+    pub fn ensure_file(&self, name: &str, csum: &[u8; 32], size: u64) -> Result<(), Error> {
+        match self.verify_file(name, csum, size)? {
+            VerifyFileResult::Ok => Ok(()),
+            mismatch => bail!("file '{}' - {}", name, mismatch),
+        }
     }
 
     // Generate canonical json
@@ -172,12 +277,26 @@ impl BackupManifest {
             manifest["signature"] = hex::encode(sig).into();
             let fingerprint = &Fingerprint::new(crypt_config.fingerprint());
             manifest["unprotected"]["key-fingerprint"] = serde_json::to_value(fingerprint)?;
+            // informational only, not covered by the signature (see `signature_time`)
+            manifest["unprotected"]["signed-at"] = proxmox_time::epoch_i64().into();
         }
 
         let manifest = serde_json::to_string_pretty(&manifest).unwrap();
         Ok(manifest)
     }
 
+    /// Returns when the manifest was last signed, if it is signed.
+    ///
+    /// Like the key fingerprint, this is stored in the unprotected part of the manifest, so that
+    /// older servers keep it when they rewrite the manifest.
+    ///
+    /// Note that the value is **not** authenticated: it is not covered by the signature and
+    /// anybody with write access to the snapshot can change it. It is purely informational and
+    /// must not be relied upon as proof of when the manifest was sealed.
+    pub fn signature_time(&self) -> Option<i64> {
+        self.unprotected["signed-at"].as_i64()
+    }
+
     pub fn fingerprint(&self) -> Result<Option<Fingerprint>, Error> {
         match &self.unprotected["key-fingerprint"] {
             Value::Null => Ok(None),
@@ -302,6 +421,7 @@ fn test_manifest_signature() -> Result<(), Error> {
     let expected_signature = hex::encode(manifest.signature(&crypt_config)?);
 
     assert_eq!(signature, expected_signature);
+    assert!(manifest.signature_time().is_some());
 
     Ok(())
 }
@@ -5,7 +5,7 @@ use anyhow::Error;
 
 use pbs_api_types::KeepOptions;
 
-use super::BackupInfo;
+use super::{BackupInfo, BackupSortField};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum PruneMark {
@@ -110,7 +110,7 @@ pub fn compute_prune_info(
 ) -> Result<Vec<(BackupInfo, PruneMark)>, Error> {
     let mut mark = HashMap::new();
 
-    BackupInfo::sort_list(&mut list, false);
+    BackupInfo::sort_list(&mut list, BackupSortField::Time, false);
 
     remove_incomplete_snapshots(&mut mark, &list);
 
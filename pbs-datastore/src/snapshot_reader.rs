@@ -66,7 +66,7 @@ impl SnapshotReader {
 
         let mut file_list = vec![MANIFEST_BLOB_NAME.to_string()];
         for item in manifest.files() {
-            file_list.push(item.filename.clone());
+            file_list.push(item.filename().to_string());
         }
         if client_log_path.exists() {
             file_list.push(CLIENT_LOG_BLOB_NAME.to_string());
@@ -149,6 +149,7 @@ impl<'a, F: Fn(&[u8; 32]) -> bool> Iterator for SnapshotChunkIterator<'a, F> {
                         let datastore = DataStore::lookup_datastore(
                             self.snapshot_reader.datastore_name(),
                             Some(Operation::Read),
+                            None,
                         )?;
                         let order =
                             datastore.get_chunks_in_order(&*index, &self.skip_fn, |_| Ok(()))?;
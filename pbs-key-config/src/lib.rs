@@ -55,6 +55,30 @@ impl KeyDerivationConfig {
     }
 }
 
+/// Errors returned by [`KeyConfig::decrypt`] which callers may want to handle.
+#[derive(Debug)]
+pub enum KeyConfigError {
+    /// The key could not be decrypted with the given passphrase.
+    ///
+    /// Note that AES-GCM cannot distinguish a wrong passphrase from corrupted key data.
+    WrongPassword { hint: Option<String> },
+}
+
+impl std::error::Error for KeyConfigError {}
+
+impl std::fmt::Display for KeyConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeyConfigError::WrongPassword { hint: Some(hint) } => {
+                write!(f, "Unable to decrypt key (password hint: {hint})")
+            }
+            KeyConfigError::WrongPassword { hint: None } => {
+                write!(f, "Unable to decrypt key (wrong password?)")
+            }
+        }
+    }
+}
+
 /// Encryption Key Configuration
 ///
 /// We use this struct to store secret keys. When used with a key
@@ -204,22 +228,15 @@ impl KeyConfig {
             let cipher = openssl::symm::Cipher::aes_256_gcm();
 
             openssl::symm::decrypt_aead(cipher, &derived_key, Some(iv), b"", enc_data, tag)
-                .map_err(|err| match self.hint {
-                    Some(ref hint) => {
-                        format_err!("Unable to decrypt key (password hint: {})", hint)
-                    }
-                    None => {
-                        format_err!("Unable to decrypt key (wrong password?) - {}", err)
-                    }
+                .map_err(|_| KeyConfigError::WrongPassword {
+                    hint: self.hint.clone(),
                 })?
         } else {
             raw_data.clone()
         };
 
-        let mut result = [0u8; 32];
-        result.copy_from_slice(&key);
-
-        let crypt_config = CryptConfig::new(result)?;
+        let crypt_config = CryptConfig::try_from_slice(&key)?;
+        let result = *crypt_config.enc_key();
         let fingerprint = Fingerprint::new(crypt_config.fingerprint());
         if let Some(ref stored_fingerprint) = self.fingerprint {
             if &fingerprint != stored_fingerprint {
@@ -11,7 +11,7 @@ use crate::{
 /// the underlying reader, and does additional error checks:
 ///
 /// - check magic number (detect streams not written by 'BlockWriter')
-/// - check block size
+/// - check block size (including a lower bound for all but the last block)
 /// - check block sequence numbers
 ///
 /// The reader consumes the EOF mark after the data stream (if read to
@@ -25,19 +25,29 @@ pub struct BlockedReader<R> {
     got_eod: bool,
     read_error: bool,
     read_pos: usize,
+    min_block_size: usize,
+    position: u64,
 }
 
 impl<R: BlockRead> BlockedReader<R> {
+    /// Default for the minimal payload size of blocks (except the last one).
+    pub const DEFAULT_MIN_BLOCK_SIZE: usize = 512;
+
     /// Create a new BlockedReader instance.
     ///
     /// This tries to read the first block. Please inspect the error
     /// to detect EOF and EOT.
-    pub fn open(mut reader: R) -> Result<Self, BlockReadError> {
+    ///
+    /// Blocks (except the last one) with a payload size smaller than `min_block_size` (default
+    /// [`DEFAULT_MIN_BLOCK_SIZE`](Self::DEFAULT_MIN_BLOCK_SIZE)) are treated as corrupt.
+    pub fn open(mut reader: R, min_block_size: Option<usize>) -> Result<Self, BlockReadError> {
+        let min_block_size = min_block_size.unwrap_or(Self::DEFAULT_MIN_BLOCK_SIZE);
+
         let mut buffer = BlockHeader::new();
 
         Self::read_block_frame(&mut buffer, &mut reader)?;
 
-        let (_size, found_end_marker) = Self::check_buffer(&buffer, 0)?;
+        let (_size, found_end_marker) = Self::check_buffer(&buffer, 0, min_block_size)?;
 
         let mut incomplete = false;
         let mut got_eod = false;
@@ -57,10 +67,21 @@ impl<R: BlockRead> BlockedReader<R> {
             seq_nr: 1,
             read_error: false,
             read_pos: 0,
+            min_block_size,
+            position: 0,
         })
     }
 
-    fn check_buffer(buffer: &BlockHeader, seq_nr: u32) -> Result<(usize, bool), std::io::Error> {
+    /// Returns the number of payload bytes returned by `read()` (or skipped) so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    fn check_buffer(
+        buffer: &BlockHeader,
+        seq_nr: u32,
+        min_block_size: usize,
+    ) -> Result<(usize, bool), std::io::Error> {
         if buffer.magic != PROXMOX_TAPE_BLOCK_HEADER_MAGIC_1_0 {
             proxmox_lang::io_bail!(
                 "got tape block with unknown magic number - not written by PBS or incompatible LTO version"
@@ -86,6 +107,12 @@ impl<R: BlockRead> BlockedReader<R> {
             );
         } else if size == 0 && !found_end_marker {
             proxmox_lang::io_bail!("detected tape block with zero payload size");
+        } else if size < min_block_size && !found_end_marker {
+            proxmox_lang::io_bail!(
+                "detected tape block with too small payload size ({} < {})",
+                size,
+                min_block_size
+            );
         }
 
         Ok((size, found_end_marker))
@@ -141,7 +168,8 @@ impl<R: BlockRead> BlockedReader<R> {
             }
         }
 
-        let (size, found_end_marker) = Self::check_buffer(&self.buffer, self.seq_nr)?;
+        let (size, found_end_marker) =
+            Self::check_buffer(&self.buffer, self.seq_nr, self.min_block_size)?;
         self.seq_nr += 1;
 
         if found_end_marker {
@@ -180,18 +208,22 @@ impl<R: BlockRead> TapeRead for BlockedReader<R> {
 
     // like ReadExt::skip_to_end(), but does not raise an error if the
     // stream has no end marker.
+    //
+    // Returns the number of payload bytes skipped, which are consumed like with read().
     fn skip_data(&mut self) -> Result<usize, std::io::Error> {
         let mut bytes = 0;
-        let buffer_size = self.buffer.size();
-        let rest = (buffer_size as isize) - (self.read_pos as isize);
-        if rest > 0 {
-            bytes = rest as usize;
-        }
         loop {
+            let buffer_size = self.buffer.size();
+            if buffer_size > self.read_pos {
+                let rest = buffer_size - self.read_pos;
+                bytes += rest;
+                self.position += rest as u64;
+                self.read_pos = buffer_size;
+            }
             if self.got_eod {
                 return Ok(bytes);
             }
-            bytes += self.read_block(false)?;
+            self.read_block(false)?;
         }
     }
 }
@@ -228,6 +260,7 @@ impl<R: BlockRead> Read for BlockedReader<R> {
             buffer[..copy_len]
                 .copy_from_slice(&self.buffer.payload[self.read_pos..(self.read_pos + copy_len)]);
             self.read_pos += copy_len;
+            self.position += copy_len as u64;
             Ok(copy_len)
         }
     }
@@ -236,8 +269,8 @@ impl<R: BlockRead> Read for BlockedReader<R> {
 #[cfg(test)]
 mod test {
     use crate::{
-        BlockReadError, BlockedReader, BlockedWriter, EmulateTapeReader, EmulateTapeWriter,
-        TapeWrite, PROXMOX_TAPE_BLOCK_SIZE,
+        BlockHeader, BlockReadError, BlockWrite, BlockedReader, BlockedWriter, EmulateTapeReader,
+        EmulateTapeWriter, MirrorError, TapeRead, TapeWrite, PROXMOX_TAPE_BLOCK_SIZE,
     };
     use anyhow::{bail, Error};
     use std::io::Read;
@@ -247,7 +280,7 @@ mod test {
 
         {
             let writer = EmulateTapeWriter::new(&mut tape_data, 1024 * 1024 * 10);
-            let mut writer = BlockedWriter::new(writer);
+            let mut writer = BlockedWriter::new(writer, None);
 
             writer.write_all(data)?;
 
@@ -262,12 +295,13 @@ mod test {
 
         let reader = &mut &tape_data[..];
         let reader = EmulateTapeReader::new(reader);
-        let mut reader = BlockedReader::open(reader)?;
+        let mut reader = BlockedReader::open(reader, None)?;
 
         let mut read_data = Vec::with_capacity(PROXMOX_TAPE_BLOCK_SIZE);
         reader.read_to_end(&mut read_data)?;
 
         assert_eq!(data.len(), read_data.len());
+        assert_eq!(reader.position(), data.len() as u64);
 
         assert_eq!(data, &read_data[..]);
 
@@ -290,12 +324,130 @@ mod test {
         write_and_verify(&data)
     }
 
+    #[test]
+    fn mirrored_write() -> Result<(), Error> {
+        let data = proxmox_sys::linux::random_data(PROXMOX_TAPE_BLOCK_SIZE * 3)?;
+        let mut tape_data1 = Vec::new();
+        let mut tape_data2 = Vec::new();
+
+        {
+            let mut writer = BlockedWriter::new_mirrored(vec![
+                Box::new(EmulateTapeWriter::new(&mut tape_data1, 1024 * 1024 * 10)),
+                Box::new(EmulateTapeWriter::new(&mut tape_data2, 1024 * 1024 * 10)),
+            ]);
+
+            writer.write_all(&data)?;
+
+            writer.finish(false)?;
+        }
+
+        assert_eq!(tape_data1, tape_data2);
+
+        let reader = &mut &tape_data2[..];
+        let reader = EmulateTapeReader::new(reader);
+        let mut reader = BlockedReader::open(reader, None)?;
+
+        let mut read_data = Vec::with_capacity(data.len());
+        reader.read_to_end(&mut read_data)?;
+        assert_eq!(data, read_data);
+
+        Ok(())
+    }
+
+    // fails every write after `blocks` successfully written blocks
+    struct FailingWriter {
+        blocks: usize,
+    }
+
+    impl BlockWrite for FailingWriter {
+        fn write_block(&mut self, _buffer: &[u8]) -> Result<bool, std::io::Error> {
+            if self.blocks == 0 {
+                proxmox_lang::io_bail!("simulated drive failure");
+            }
+            self.blocks -= 1;
+            Ok(false)
+        }
+
+        fn write_filemark(&mut self) -> Result<(), std::io::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn mirrored_write_with_failing_drive() -> Result<(), Error> {
+        let data = proxmox_sys::linux::random_data(PROXMOX_TAPE_BLOCK_SIZE * 3)?;
+        let mut tape_data = Vec::new();
+
+        {
+            let mut writer = BlockedWriter::new_mirrored(vec![
+                Box::new(EmulateTapeWriter::new(&mut tape_data, 1024 * 1024 * 10)),
+                Box::new(FailingWriter { blocks: 1 }),
+            ]);
+
+            // the failure is not reported while writing, the remaining drive continues
+            writer.write_all(&data)?;
+
+            let err = match writer.finish(false) {
+                Ok(_) => bail!("expected finish to report the failed drive"),
+                Err(err) => err,
+            };
+            let err = err
+                .get_ref()
+                .and_then(|err| err.downcast_ref::<MirrorError>())
+                .expect("expected a MirrorError");
+            assert_eq!(err.failed_drives, 0b10);
+        }
+
+        let reader = &mut &tape_data[..];
+        let reader = EmulateTapeReader::new(reader);
+        let mut reader = BlockedReader::open(reader, None)?;
+
+        let mut read_data = Vec::with_capacity(data.len());
+        reader.read_to_end(&mut read_data)?;
+        assert_eq!(data, read_data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn skip_data() -> Result<(), Error> {
+        // ten full blocks and a partial one
+        let data_len = PROXMOX_TAPE_BLOCK_SIZE * 10 + 100;
+        let data = proxmox_sys::linux::random_data(data_len)?;
+        let mut tape_data = Vec::new();
+
+        {
+            let writer = EmulateTapeWriter::new(&mut tape_data, 1024 * 1024 * 10);
+            let mut writer = BlockedWriter::new(writer, None);
+
+            writer.write_all(&data)?;
+
+            writer.finish(false)?;
+        }
+
+        let reader = &mut &tape_data[..];
+        let reader = EmulateTapeReader::new(reader);
+        let mut reader = BlockedReader::open(reader, None)?;
+
+        let mut buf = [0u8; 10];
+        reader.read_exact(&mut buf)?;
+
+        assert_eq!(reader.skip_data()?, data_len - buf.len());
+        assert_eq!(reader.position(), data_len as u64);
+
+        // everything is consumed now
+        assert_eq!(reader.skip_data()?, 0);
+        assert_eq!(reader.read(&mut buf)?, 0);
+
+        Ok(())
+    }
+
     #[test]
     fn no_data() -> Result<(), Error> {
         let tape_data = Vec::new();
         let reader = &mut &tape_data[..];
         let reader = EmulateTapeReader::new(reader);
-        match BlockedReader::open(reader) {
+        match BlockedReader::open(reader, None) {
             Err(BlockReadError::EndOfFile) => { /* OK */ }
             _ => bail!("expected EOF"),
         }
@@ -308,7 +460,7 @@ mod test {
         let mut tape_data = Vec::new();
         {
             let writer = EmulateTapeWriter::new(&mut tape_data, 1024 * 1024);
-            let mut writer = BlockedWriter::new(writer);
+            let mut writer = BlockedWriter::new(writer, None);
             // write at least one block
             let data = proxmox_sys::linux::random_data(PROXMOX_TAPE_BLOCK_SIZE)?;
             writer.write_all(&data)?;
@@ -317,7 +469,7 @@ mod test {
 
         let reader = &mut &tape_data[..];
         let reader = EmulateTapeReader::new(reader);
-        let mut reader = BlockedReader::open(reader)?;
+        let mut reader = BlockedReader::open(reader, None)?;
 
         let mut data = Vec::with_capacity(PROXMOX_TAPE_BLOCK_SIZE);
         assert!(reader.read_to_end(&mut data).is_err());
@@ -325,13 +477,48 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn too_small_block() -> Result<(), Error> {
+        let mut tape_data = Vec::new();
+        {
+            let mut writer = EmulateTapeWriter::new(&mut tape_data, 1024 * 1024);
+            let mut block = BlockHeader::new();
+            block.set_size(1);
+            block.set_seq_nr(0);
+            let data = unsafe {
+                std::slice::from_raw_parts(
+                    (&*block as *const BlockHeader) as *const u8,
+                    BlockHeader::SIZE,
+                )
+            };
+            writer.write_block(data)?;
+            writer.write_filemark()?;
+        }
+
+        let reader = &mut &tape_data[..];
+        let reader = EmulateTapeReader::new(reader);
+        match BlockedReader::open(reader, None) {
+            Err(BlockReadError::Error(err)) => {
+                let msg = err.to_string();
+                assert!(
+                    msg.contains("too small payload size (1 < "),
+                    "unexpected error: {msg}"
+                );
+            }
+            Err(err) => bail!("unexpected error: {err}"),
+            Ok(_) => bail!("expected block below min_block_size to be rejected"),
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn small_read_buffer() -> Result<(), Error> {
         let mut tape_data = Vec::new();
 
         {
             let writer = EmulateTapeWriter::new(&mut tape_data, 1024 * 1024);
-            let mut writer = BlockedWriter::new(writer);
+            let mut writer = BlockedWriter::new(writer, None);
 
             writer.write_all(b"ABC")?;
 
@@ -340,7 +527,7 @@ mod test {
 
         let reader = &mut &tape_data[..];
         let reader = EmulateTapeReader::new(reader);
-        let mut reader = BlockedReader::open(reader)?;
+        let mut reader = BlockedReader::open(reader, None)?;
 
         let mut buf = [0u8; 1];
         assert_eq!(reader.read(&mut buf)?, 1, "wrong byte count");
@@ -1,6 +1,150 @@
+use std::time::Duration;
+
 use proxmox_io::vec;
 
-use crate::{BlockHeader, BlockHeaderFlags, BlockWrite, TapeWrite};
+use crate::{
+    BlockHeader, BlockHeaderFlags, BlockWrite, TapeWrite, PROXMOX_TAPE_BLOCK_HEADER_MAGIC_1_0,
+};
+
+/// Retry policy for transient `WouldBlock` errors of the underlying writer
+#[derive(Clone, Copy, Debug)]
+pub struct WriteRetryPolicy {
+    /// Number of retries before the error is returned to the caller
+    pub retries: usize,
+    /// Time to wait between two attempts
+    pub backoff: Duration,
+}
+
+impl Default for WriteRetryPolicy {
+    /// Retry up to 10 times, waiting one second in between.
+    fn default() -> Self {
+        Self {
+            retries: 10,
+            backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Error returned by [MirrorBlockWriter] if writing to one or more drives failed
+#[derive(Debug)]
+pub struct MirrorError {
+    /// Bitmask of all drives which failed so far (bit N set means drive N failed)
+    pub failed_drives: u64,
+    /// The last error reported by a drive
+    pub last_error: std::io::Error,
+}
+
+impl std::error::Error for MirrorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.last_error)
+    }
+}
+
+impl std::fmt::Display for MirrorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "mirrored write failed on drive mask {:#x} - {}",
+            self.failed_drives, self.last_error
+        )
+    }
+}
+
+/// Write the same blocks to multiple drives
+///
+/// A block is always written to all drives which did not fail before. Once a drive fails, it is
+/// excluded from further writes and writing continues on the remaining drives. The failure is
+/// reported as [MirrorError] (wrapped in a `std::io::Error`) when writing the next filemark, so
+/// a [BlockedWriter] using it returns the error from `finish()`. An error is only returned
+/// immediately if no working drive is left.
+pub struct MirrorBlockWriter<'a> {
+    drives: Vec<Box<dyn BlockWrite + 'a>>,
+    failed_drives: u64,
+    last_error: Option<std::io::Error>,
+}
+
+impl<'a> MirrorBlockWriter<'a> {
+    /// Creates a new instance.
+    ///
+    /// Panics if `drives` is empty or contains more than 64 drives.
+    pub fn new(drives: Vec<Box<dyn BlockWrite + 'a>>) -> Self {
+        assert!(
+            !drives.is_empty() && drives.len() <= 64,
+            "mirroring needs between 1 and 64 drives"
+        );
+        Self {
+            drives,
+            failed_drives: 0,
+            last_error: None,
+        }
+    }
+
+    /// Bitmask of all drives which failed so far
+    pub fn failed_drives(&self) -> u64 {
+        self.failed_drives
+    }
+
+    fn write_each<F, R>(&mut self, mut func: F) -> Result<Vec<R>, std::io::Error>
+    where
+        F: FnMut(&mut dyn BlockWrite) -> Result<R, std::io::Error>,
+    {
+        let mut results = Vec::with_capacity(self.drives.len());
+
+        for (idx, drive) in self.drives.iter_mut().enumerate() {
+            let mask = 1u64 << idx;
+            if self.failed_drives & mask != 0 {
+                continue;
+            }
+            match func(drive.as_mut()) {
+                Ok(result) => results.push(result),
+                Err(err) => {
+                    self.failed_drives |= mask;
+                    self.last_error = Some(err);
+                }
+            }
+        }
+
+        if results.is_empty() {
+            return Err(self.take_error().unwrap_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "mirrored write failed - no working drive left",
+                )
+            }));
+        }
+
+        Ok(results)
+    }
+
+    // returns the recorded failures (if any) as `MirrorError`
+    fn take_error(&mut self) -> Option<std::io::Error> {
+        let last_error = self.last_error.take()?;
+        Some(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            MirrorError {
+                failed_drives: self.failed_drives,
+                last_error,
+            },
+        ))
+    }
+}
+
+impl<'a> BlockWrite for MirrorBlockWriter<'a> {
+    /// Returns true if any of the drives reached the Logical End Of Media
+    fn write_block(&mut self, buffer: &[u8]) -> Result<bool, std::io::Error> {
+        let results = self.write_each(|drive| drive.write_block(buffer))?;
+        Ok(results.into_iter().any(|leom| leom))
+    }
+
+    /// Returns the [MirrorError] if any drive failed since the last filemark
+    fn write_filemark(&mut self) -> Result<(), std::io::Error> {
+        self.write_each(|drive| drive.write_filemark())?;
+        match self.take_error() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
 
 /// Assemble and write blocks of data
 ///
@@ -15,6 +159,7 @@ pub struct BlockedWriter<W: BlockWrite> {
     logical_end_of_media: bool,
     bytes_written: usize,
     wrote_eof: bool,
+    retry_policy: Option<WriteRetryPolicy>,
 }
 
 impl<W: BlockWrite> Drop for BlockedWriter<W> {
@@ -33,7 +178,9 @@ impl<W: BlockWrite> BlockedWriter<W> {
     }
 
     /// Creates a new instance.
-    pub fn new(writer: W) -> Self {
+    ///
+    /// If `retry_policy` is set, writes failing with `WouldBlock` are retried accordingly.
+    pub fn new(writer: W, retry_policy: Option<WriteRetryPolicy>) -> Self {
         Self {
             writer,
             buffer: BlockHeader::new(),
@@ -42,17 +189,36 @@ impl<W: BlockWrite> BlockedWriter<W> {
             logical_end_of_media: false,
             bytes_written: 0,
             wrote_eof: false,
+            retry_policy,
         }
     }
 
-    fn write_block(buffer: &BlockHeader, writer: &mut W) -> Result<bool, std::io::Error> {
+    fn write_block(
+        buffer: &BlockHeader,
+        writer: &mut W,
+        retry_policy: Option<WriteRetryPolicy>,
+    ) -> Result<bool, std::io::Error> {
+        debug_assert_eq!(buffer.magic, PROXMOX_TAPE_BLOCK_HEADER_MAGIC_1_0);
         let data = unsafe {
             std::slice::from_raw_parts(
                 (buffer as *const BlockHeader) as *const u8,
                 BlockHeader::SIZE,
             )
         };
-        writer.write_block(data)
+
+        let (mut retries, backoff) = match retry_policy {
+            Some(policy) => (policy.retries, policy.backoff),
+            None => (0, Duration::ZERO),
+        };
+        loop {
+            match writer.write_block(data) {
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock && retries > 0 => {
+                    retries -= 1;
+                    std::thread::sleep(backoff);
+                }
+                result => return result,
+            }
+        }
     }
 
     fn write_eof(&mut self) -> Result<(), std::io::Error> {
@@ -80,8 +246,9 @@ impl<W: BlockWrite> BlockedWriter<W> {
             self.buffer.flags = BlockHeaderFlags::empty();
             self.buffer.set_size(self.buffer.payload.len());
             self.buffer.set_seq_nr(self.seq_nr);
+            // only advance once the block is written, so that a failed block can be retried
+            let leom = Self::write_block(&self.buffer, &mut self.writer, self.retry_policy)?;
             self.seq_nr += 1;
-            let leom = Self::write_block(&self.buffer, &mut self.writer)?;
             if leom {
                 self.logical_end_of_media = true;
             }
@@ -95,6 +262,13 @@ impl<W: BlockWrite> BlockedWriter<W> {
     }
 }
 
+impl<'a> BlockedWriter<MirrorBlockWriter<'a>> {
+    /// Creates a new instance writing each block to all `drives`, see [MirrorBlockWriter].
+    pub fn new_mirrored(drives: Vec<Box<dyn BlockWrite + 'a>>) -> Self {
+        Self::new(MirrorBlockWriter::new(drives), None)
+    }
+}
+
 impl<W: BlockWrite> TapeWrite for BlockedWriter<W> {
     fn write_all(&mut self, mut data: &[u8]) -> Result<bool, std::io::Error> {
         while !data.is_empty() {
@@ -122,9 +296,9 @@ impl<W: BlockWrite> TapeWrite for BlockedWriter<W> {
         }
         self.buffer.set_size(self.buffer_pos);
         self.buffer.set_seq_nr(self.seq_nr);
+        let leom = Self::write_block(&self.buffer, &mut self.writer, self.retry_policy)?;
         self.seq_nr += 1;
         self.bytes_written += BlockHeader::SIZE;
-        let leom = Self::write_block(&self.buffer, &mut self.writer)?;
         self.write_eof()?;
         Ok(leom)
     }
@@ -18,7 +18,7 @@ mod blocked_reader;
 pub use blocked_reader::BlockedReader;
 
 mod blocked_writer;
-pub use blocked_writer::BlockedWriter;
+pub use blocked_writer::{BlockedWriter, MirrorBlockWriter, MirrorError, WriteRetryPolicy};
 
 mod tape_write;
 pub use tape_write::*;
@@ -39,6 +39,12 @@ pub mod sg_pt_changer;
 /// We use 256KB blocksize (always)
 pub const PROXMOX_TAPE_BLOCK_SIZE: usize = 256 * 1024;
 
+// tape drives require block sizes to be a multiple of 512 bytes
+const _: () = assert!(
+    PROXMOX_TAPE_BLOCK_SIZE % 512 == 0,
+    "tape block size is not a multiple of 512"
+);
+
 // openssl::sha::sha256(b"Proxmox Tape Block Header v1.0")[0..8]
 pub const PROXMOX_TAPE_BLOCK_HEADER_MAGIC_1_0: [u8; 8] = [220, 189, 175, 202, 235, 160, 165, 40];
 
@@ -42,7 +42,7 @@ use crate::{
         scsi_mode_sense, scsi_request_sense, InquiryInfo, ModeBlockDescriptor, ModeParameterHeader,
         ScsiError, SenseInfo, SgRaw,
     },
-    BlockRead, BlockReadError, BlockWrite, BlockedReader, BlockedWriter,
+    BlockRead, BlockReadError, BlockWrite, BlockedReader, BlockedWriter, WriteRetryPolicy,
 };
 
 #[repr(C, packed)]
@@ -785,12 +785,12 @@ impl SgTape {
 
     pub fn open_writer(&mut self) -> BlockedWriter<SgTapeWriter> {
         let writer = SgTapeWriter::new(self);
-        BlockedWriter::new(writer)
+        BlockedWriter::new(writer, Some(WriteRetryPolicy::default()))
     }
 
     pub fn open_reader(&mut self) -> Result<BlockedReader<SgTapeReader>, BlockReadError> {
         let reader = SgTapeReader::new(self);
-        BlockedReader::open(reader)
+        BlockedReader::open(reader, None)
     }
 
     /// Set all options we need/want
@@ -7,6 +7,8 @@
 //! encryption](https://en.wikipedia.org/wiki/Authenticated_encryption)
 //! for a short introduction.
 
+use std::fmt;
+
 use anyhow::Error;
 use openssl::hash::MessageDigest;
 use openssl::pkcs5::pbkdf2_hmac;
@@ -19,6 +21,48 @@ const FINGERPRINT_INPUT: [u8; 32] = [
     114, 93, 223, 48, 153, 45, 37, 236, 69, 237, 38,
 ];
 
+/// Error returned when trying to create a [`CryptConfig`] from a key with the wrong length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyLengthError {
+    /// The length of the rejected key
+    pub length: usize,
+}
+
+impl std::error::Error for KeyLengthError {}
+
+impl fmt::Display for KeyLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "got strange key length ({} != 32)", self.length)
+    }
+}
+
+/// Error returned by [`CryptConfig::try_from_slice`].
+#[derive(Debug)]
+pub enum TryFromSliceError {
+    /// The key does not have the expected length
+    KeyLength(KeyLengthError),
+    /// Setting up the crypto context failed
+    Crypto(Error),
+}
+
+impl std::error::Error for TryFromSliceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::KeyLength(err) => Some(err),
+            Self::Crypto(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl fmt::Display for TryFromSliceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::KeyLength(err) => write!(f, "{err}"),
+            Self::Crypto(err) => write!(f, "{err}"),
+        }
+    }
+}
+
 /// Encryption Configuration with secret key
 ///
 /// This structure stores the secret key and provides helpers for
@@ -60,6 +104,16 @@ impl CryptConfig {
         })
     }
 
+    /// Create a new instance from a key slice of unknown length.
+    ///
+    /// Fails with [`TryFromSliceError::KeyLength`] if the key is not exactly 32 bytes long.
+    pub fn try_from_slice(key: &[u8]) -> Result<Self, TryFromSliceError> {
+        let enc_key: [u8; 32] = key
+            .try_into()
+            .map_err(|_| TryFromSliceError::KeyLength(KeyLengthError { length: key.len() }))?;
+        Self::new(enc_key).map_err(TryFromSliceError::Crypto)
+    }
+
     /// Expose Cipher (AES_256_GCM)
     pub fn cipher(&self) -> &Cipher {
         &self.cipher
@@ -115,3 +169,16 @@ impl CryptConfig {
         Ok(crypter)
     }
 }
+
+#[test]
+fn test_try_from_slice_key_length() {
+    assert!(CryptConfig::try_from_slice(&[0u8; 32]).is_ok());
+
+    for length in [16, 33] {
+        match CryptConfig::try_from_slice(&vec![0u8; length]) {
+            Err(TryFromSliceError::KeyLength(err)) => assert_eq!(err.length, length),
+            Err(err) => panic!("unexpected error for key length {length} - {err}"),
+            Ok(_) => panic!("key with length {length} was accepted"),
+        }
+    }
+}
@@ -228,7 +228,7 @@ async fn catalog_shell(param: Value) -> Result<(), Error> {
 
     // Note: do not use values stored in index (not trusted) - instead, computed them again
     let (csum, size) = index.compute_csum();
-    manifest.verify_file(CATALOG_NAME, &csum, size)?;
+    manifest.ensure_file(CATALOG_NAME, &csum, size)?;
 
     let most_used = index.find_most_used_chunks(8);
 
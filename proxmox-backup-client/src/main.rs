@@ -37,8 +37,8 @@ use pbs_client::tools::{
     complete_img_archive_name, complete_namespace, complete_pxar_archive_name, complete_repository,
     connect, connect_rate_limited, extract_repository_from_value,
     key_source::{
-        crypto_parameters, format_key_source, get_encryption_key_password, KEYFD_SCHEMA,
-        KEYFILE_SCHEMA, MASTER_PUBKEY_FD_SCHEMA, MASTER_PUBKEY_FILE_SCHEMA,
+        crypto_parameters, format_key_source, get_encryption_key_password, CRYPT_MODE_SCHEMA,
+        KEYFD_SCHEMA, KEYFILE_SCHEMA, MASTER_PUBKEY_FD_SCHEMA, MASTER_PUBKEY_FILE_SCHEMA,
     },
     CHUNK_SIZE_SCHEMA, REPO_URL_SCHEMA,
 };
@@ -607,7 +607,7 @@ fn spawn_catalog_upload(
                optional: true,
            },
            "crypt-mode": {
-               type: CryptMode,
+               schema: CRYPT_MODE_SCHEMA,
                optional: true,
            },
            "skip-lost-and-found": {
@@ -632,6 +632,12 @@ fn spawn_catalog_upload(
                schema: BACKUP_TIME_SCHEMA,
                optional: true,
            },
+           "allow-future-time": {
+               type: Boolean,
+               description: "Allow a backup time more than 5 minutes in the future.",
+               optional: true,
+               default: false,
+           },
            "chunk-size": {
                schema: CHUNK_SIZE_SCHEMA,
                optional: true,
@@ -681,6 +687,7 @@ async fn create_backup(
     skip_lost_and_found: bool,
     dry_run: bool,
     skip_e2big_xattr: bool,
+    allow_future_time: bool,
     _info: &ApiMethod,
     _rpcenv: &mut dyn RpcEnvironment,
 ) -> Result<Value, Error> {
@@ -897,6 +904,7 @@ async fn create_backup(
         &snapshot,
         true,
         false,
+        allow_future_time,
     )
     .await?;
 
@@ -1214,7 +1222,7 @@ We do not extract '.pxar' archives when writing to standard output.
                 optional: true,
             },
             "crypt-mode": {
-                type: CryptMode,
+                schema: CRYPT_MODE_SCHEMA,
                 optional: true,
             },
             "ignore-acls": {
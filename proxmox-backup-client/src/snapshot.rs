@@ -8,7 +8,7 @@ use proxmox_schema::api;
 use proxmox_sys::fs::file_get_contents;
 
 use pbs_api_types::{BackupGroup, BackupNamespace, CryptMode, SnapshotListItem};
-use pbs_client::tools::key_source::get_encryption_key_password;
+use pbs_client::tools::key_source::{get_encryption_key_password, CRYPT_MODE_SCHEMA};
 use pbs_datastore::DataBlob;
 use pbs_key_config::decrypt_key;
 use pbs_tools::crypt_config::CryptConfig;
@@ -225,7 +225,7 @@ async fn forget_snapshots(param: Value) -> Result<(), Error> {
                 optional: true,
             },
             "crypt-mode": {
-                type: CryptMode,
+                schema: CRYPT_MODE_SCHEMA,
                 optional: true,
             },
         }
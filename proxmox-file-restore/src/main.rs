@@ -20,13 +20,13 @@ use proxmox_sys::fs::{create_path, CreateOptions};
 use pxar::accessor::aio::Accessor;
 use pxar::decoder::aio::Decoder;
 
-use pbs_api_types::{file_restore::FileRestoreFormat, BackupDir, BackupNamespace, CryptMode};
+use pbs_api_types::{file_restore::FileRestoreFormat, BackupDir, BackupNamespace};
 use pbs_client::pxar::{create_tar, create_zip, extract_sub_dir, extract_sub_dir_seq};
 use pbs_client::tools::{
     complete_group_or_snapshot, complete_repository, connect, extract_repository_from_value,
     key_source::{
-        crypto_parameters_keep_fd, format_key_source, get_encryption_key_password, KEYFD_SCHEMA,
-        KEYFILE_SCHEMA,
+        crypto_parameters_keep_fd, format_key_source, get_encryption_key_password,
+        CRYPT_MODE_SCHEMA, KEYFD_SCHEMA, KEYFILE_SCHEMA,
     },
     REPO_URL_SCHEMA,
 };
@@ -123,11 +123,13 @@ async fn list_files(
         ExtractPath::ListArchives => {
             let mut entries = vec![];
             for file in manifest.files() {
-                if !file.filename.ends_with(".pxar.didx") && !file.filename.ends_with(".img.fidx") {
+                if !file.filename().ends_with(".pxar.didx")
+                    && !file.filename().ends_with(".img.fidx")
+                {
                     continue;
                 }
-                let path = format!("/{}", file.filename);
-                let attr = if file.filename.ends_with(".pxar.didx") {
+                let path = format!("/{}", file.filename());
+                let attr = if file.filename().ends_with(".pxar.didx") {
                     // a pxar file is a file archive, so it's root is also a directory root
                     Some(&DirEntryAttribute::Directory { start: 0 })
                 } else {
@@ -136,7 +138,7 @@ async fn list_files(
                 entries.push(ArchiveEntry::new_with_size(
                     path.as_bytes(),
                     attr,
-                    Some(file.size),
+                    Some(file.size()),
                 ));
             }
 
@@ -209,7 +211,7 @@ async fn list_files(
                 optional: true,
             },
             "crypt-mode": {
-                type: CryptMode,
+                schema: CRYPT_MODE_SCHEMA,
                 optional: true,
             },
             "driver": {
@@ -311,8 +313,12 @@ async fn list(
         .sortby("text", false)
         .column(ColumnConfig::new("type"))
         .column(ColumnConfig::new("text").header("name"))
-        .column(ColumnConfig::new("mtime").header("last modified"))
-        .column(ColumnConfig::new("size"));
+        .column(
+            ColumnConfig::new("mtime")
+                .header("last modified")
+                .renderer(pbs_tools::format::render_epoch),
+        )
+        .column(ColumnConfig::new("size").renderer(pbs_tools::format::render_bytes_human_readable));
 
     let output_format = get_output_format(&param);
     format_and_print_result_full(
@@ -374,7 +380,7 @@ async fn list(
                 optional: true,
             },
             "crypt-mode": {
-                type: CryptMode,
+                schema: CRYPT_MODE_SCHEMA,
                 optional: true,
             },
             verbose: {
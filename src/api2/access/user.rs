@@ -7,13 +7,13 @@ use serde_json::{json, Value};
 use std::collections::HashMap;
 
 use proxmox_router::{ApiMethod, Permission, Router, RpcEnvironment, SubdirMap};
-use proxmox_schema::api;
+use proxmox_schema::{api, param_bail};
 use proxmox_tfa::api::TfaConfig;
 
 use pbs_api_types::{
     ApiToken, Authid, Tokenname, User, UserUpdater, UserWithTokens, Userid, ENABLE_USER_SCHEMA,
     EXPIRE_USER_SCHEMA, PBS_PASSWORD_SCHEMA, PRIV_PERMISSIONS_MODIFY, PRIV_SYS_AUDIT,
-    PROXMOX_CONFIG_DIGEST_SCHEMA, SINGLE_LINE_COMMENT_SCHEMA,
+    PROXMOX_CONFIG_DIGEST_SCHEMA, SINGLE_LINE_COMMENT_SCHEMA, USER_EMAIL_INPUT_SCHEMA,
 };
 use pbs_config::token_shadow;
 
@@ -119,6 +119,14 @@ pub fn list_users(
     Ok(list)
 }
 
+/// Check an e-mail address entered via the API against [`USER_EMAIL_INPUT_SCHEMA`].
+fn check_user_email(email: &str) -> Result<(), Error> {
+    if let Err(err) = USER_EMAIL_INPUT_SCHEMA.verify_json(&Value::from(email)) {
+        param_bail!("email", "invalid e-mail address - {err}");
+    }
+    Ok(())
+}
+
 #[api(
     protected: true,
     input: {
@@ -143,6 +151,10 @@ pub fn create_user(
     config: User,
     rpcenv: &mut dyn RpcEnvironment,
 ) -> Result<(), Error> {
+    if let Some(email) = &config.email {
+        check_user_email(email)?;
+    }
+
     let _lock = pbs_config::user::lock_config()?;
 
     let (mut section_config, _digest) = pbs_config::user::config()?;
@@ -333,7 +345,12 @@ pub async fn update_user(
         };
     }
     if let Some(email) = update.email {
-        data.email = if email.is_empty() { None } else { Some(email) };
+        data.email = if email.is_empty() {
+            None
+        } else {
+            check_user_email(&email)?;
+            Some(email)
+        };
     }
 
     config.set_data(userid.as_str(), "user", &data)?;
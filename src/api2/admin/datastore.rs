@@ -17,6 +17,7 @@ use tokio_stream::wrappers::ReceiverStream;
 use proxmox_async::blocking::WrappedReaderStream;
 use proxmox_async::{io::AsyncChannelWriter, stream::AsyncReaderStream};
 use proxmox_compression::zstd::ZstdEncoder;
+use proxmox_human_byte::HumanByte;
 use proxmox_router::{
     http_err, list_subdirs_api_method, ApiHandler, ApiMethod, ApiResponseFuture, Permission,
     Router, RpcEnvironment, RpcEnvironmentType, SubdirMap,
@@ -35,13 +36,13 @@ use pxar::EntryKind;
 use pbs_api_types::{
     print_ns_and_snapshot, print_store_and_ns, Authid, BackupContent, BackupNamespace, BackupType,
     Counts, CryptMode, DataStoreConfig, DataStoreListItem, DataStoreStatus,
-    GarbageCollectionJobStatus, GroupListItem, JobScheduleStatus, KeepOptions, Operation,
-    PruneJobOptions, RRDMode, RRDTimeFrame, SnapshotListItem, SnapshotVerifyState,
-    BACKUP_ARCHIVE_NAME_SCHEMA, BACKUP_ID_SCHEMA, BACKUP_NAMESPACE_SCHEMA, BACKUP_TIME_SCHEMA,
-    BACKUP_TYPE_SCHEMA, DATASTORE_SCHEMA, IGNORE_VERIFIED_BACKUPS_SCHEMA, MAX_NAMESPACE_DEPTH,
-    NS_MAX_DEPTH_SCHEMA, PRIV_DATASTORE_AUDIT, PRIV_DATASTORE_BACKUP, PRIV_DATASTORE_MODIFY,
-    PRIV_DATASTORE_PRUNE, PRIV_DATASTORE_READ, PRIV_DATASTORE_VERIFY, UPID, UPID_SCHEMA,
-    VERIFICATION_OUTDATED_AFTER_SCHEMA,
+    GarbageCollectionJobStatus, GroupListItem, GroupStats, JobScheduleStatus, KeepOptions,
+    Operation, PruneJobOptions, RRDMode, RRDTimeFrame, SnapshotFileSize, SnapshotListItem,
+    SnapshotVerifyState, BACKUP_ARCHIVE_NAME_SCHEMA, BACKUP_ID_SCHEMA, BACKUP_NAMESPACE_SCHEMA,
+    BACKUP_TIME_SCHEMA, BACKUP_TYPE_SCHEMA, DATASTORE_SCHEMA, IGNORE_VERIFIED_BACKUPS_SCHEMA,
+    MAX_NAMESPACE_DEPTH, NS_MAX_DEPTH_SCHEMA, PRIV_DATASTORE_AUDIT, PRIV_DATASTORE_BACKUP,
+    PRIV_DATASTORE_MODIFY, PRIV_DATASTORE_PRUNE, PRIV_DATASTORE_READ, PRIV_DATASTORE_VERIFY, UPID,
+    UPID_SCHEMA, VERIFICATION_OUTDATED_AFTER_SCHEMA,
 };
 use pbs_client::pxar::{create_tar, create_zip};
 use pbs_config::CachedUserInfo;
@@ -98,7 +99,7 @@ fn check_privs_and_load_store(
 ) -> Result<Arc<DataStore>, Error> {
     let limited = check_ns_privs_full(store, ns, auth_id, full_access_privs, partial_access_privs)?;
 
-    let datastore = DataStore::lookup_datastore(store, operation)?;
+    let datastore = DataStore::lookup_datastore(store, operation, Some(ns))?;
 
     if limited {
         let owner = datastore.get_owner(ns, backup_group)?;
@@ -116,9 +117,9 @@ fn read_backup_index(
     let mut result = Vec::new();
     for item in manifest.files() {
         result.push(BackupContent {
-            filename: item.filename.clone(),
-            crypt_mode: Some(item.crypt_mode),
-            size: Some(item.size),
+            filename: item.filename().to_string(),
+            crypt_mode: Some(item.crypt_mode()),
+            size: Some(item.size()),
         });
     }
 
@@ -168,6 +169,16 @@ fn get_all_snapshot_files(
                 type: BackupNamespace,
                 optional: true,
             },
+            offset: {
+                type: u64,
+                description: "Skip this amount of accessible groups.",
+                optional: true,
+            },
+            limit: {
+                type: u64,
+                description: "Only list this amount of groups.",
+                optional: true,
+            },
         },
     },
     returns: pbs_api_types::ADMIN_DATASTORE_LIST_GROUPS_RETURN_TYPE,
@@ -178,9 +189,14 @@ fn get_all_snapshot_files(
     },
 )]
 /// List backup groups.
+///
+/// Groups are listed in directory order, `offset` and `limit` are applied to the groups the user
+/// has access to. Groups without snapshots are omitted, so a page may contain less entries.
 pub fn list_groups(
     store: String,
     ns: Option<BackupNamespace>,
+    offset: Option<u64>,
+    limit: Option<u64>,
     rpcenv: &mut dyn RpcEnvironment,
 ) -> Result<Vec<GroupListItem>, Error> {
     let auth_id: Authid = rpcenv.get_auth_id().unwrap().parse()?;
@@ -194,12 +210,18 @@ pub fn list_groups(
         PRIV_DATASTORE_BACKUP,
     )?;
 
-    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read))?;
+    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read), None)?;
+
+    let offset = offset.unwrap_or(0) as usize;
+    let limit = limit.map_or(usize::MAX, |limit| limit as usize);
 
     datastore
         .iter_backup_groups(ns.clone())? // FIXME: Namespaces and recursion parameters!
-        .try_fold(Vec::new(), |mut group_info, group| {
-            let group = group?;
+        .filter_map(|group| {
+            let group = match group {
+                Ok(group) => group,
+                Err(err) => return Some(Err(err)),
+            };
 
             let owner = match datastore.get_owner(&ns, group.as_ref()) {
                 Ok(auth_id) => auth_id,
@@ -210,13 +232,20 @@ pub fn list_groups(
                         print_store_and_ns(&store, &ns),
                         err
                     );
-                    return Ok(group_info);
+                    return None;
                 }
             };
             if !list_all && check_backup_owner(&owner, &auth_id).is_err() {
-                return Ok(group_info);
+                return None;
             }
 
+            Some(Ok((group, owner)))
+        })
+        .skip(offset)
+        .take(limit)
+        .try_fold(Vec::new(), |mut group_info, entry| {
+            let (group, owner) = entry?;
+
             let snapshots = match group.list_backups() {
                 Ok(snapshots) => snapshots,
                 Err(_) => return Ok(group_info),
@@ -476,13 +505,13 @@ unsafe fn list_snapshots_blocking(
         PRIV_DATASTORE_BACKUP,
     )?;
 
-    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read))?;
+    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read), None)?;
 
     // FIXME: filter also owner before collecting, for doing that nicely the owner should move into
     // backup group and provide an error free (Err -> None) accessor
     let groups = match (backup_type, backup_id) {
         (Some(backup_type), Some(backup_id)) => {
-            vec![datastore.backup_group_from_parts(ns.clone(), backup_type, backup_id)]
+            vec![datastore.backup_group_from_parts(ns.clone(), backup_type, backup_id)?]
         }
         // FIXME: Recursion
         (Some(backup_type), None) => datastore
@@ -491,11 +520,13 @@ unsafe fn list_snapshots_blocking(
         // FIXME: Recursion
         (None, Some(backup_id)) => BackupType::iter()
             .filter_map(|backup_type| {
-                let group =
-                    datastore.backup_group_from_parts(ns.clone(), backup_type, backup_id.clone());
-                group.exists().then_some(group)
+                match datastore.backup_group_from_parts(ns.clone(), backup_type, backup_id.clone())
+                {
+                    Ok(group) => group.exists().then_some(Ok(group)),
+                    Err(err) => Some(Err(err)),
+                }
             })
-            .collect(),
+            .collect::<Result<_, Error>>()?,
         // FIXME: Recursion
         (None, None) => datastore.list_backup_groups(ns.clone())?,
     };
@@ -690,7 +721,7 @@ pub async fn status(
         }
     };
 
-    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read))?;
+    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read), None)?;
 
     let (counts, gc_status) = if verbose {
         let filter_owner = if store_privs & PRIV_DATASTORE_AUDIT != 0 {
@@ -731,6 +762,34 @@ pub async fn status(
     })
 }
 
+/// Reconstruct the manifest of `backup_dir` if it is missing, logging the outcome.
+fn repair_snapshot_manifest(worker: &WorkerTask, datastore: &DataStore, backup_dir: &BackupDir) {
+    let mut path = backup_dir.full_path();
+    path.push(MANIFEST_BLOB_NAME);
+    if path.exists() {
+        return;
+    }
+
+    match datastore.repair_missing_manifest(backup_dir) {
+        Ok(()) => task_log!(worker, "repaired manifest of snapshot {}", backup_dir.dir()),
+        Err(err) => task_warn!(
+            worker,
+            "unable to repair manifest of snapshot {} - {}",
+            backup_dir.dir(),
+            err
+        ),
+    }
+}
+
+/// Datastore operation required by a verify, repairing writes to the datastore.
+fn verify_operation(repair: bool) -> Operation {
+    if repair {
+        Operation::Write
+    } else {
+        Operation::Read
+    }
+}
+
 #[api(
     input: {
         properties: {
@@ -765,6 +824,12 @@ pub async fn status(
                 schema: NS_MAX_DEPTH_SCHEMA,
                 optional: true,
             },
+            repair: {
+                type: bool,
+                default: false,
+                optional: true,
+                description: "Try to reconstruct missing manifests before verification.",
+            },
         },
     },
     returns: {
@@ -773,7 +838,8 @@ pub async fn status(
     access: {
         permission: &Permission::Anybody,
         description: "Requires on /datastore/{store}[/{namespace}] either DATASTORE_VERIFY for any \
-            or DATASTORE_BACKUP and being the owner of the group",
+            or DATASTORE_BACKUP and being the owner of the group. Repairing additionally requires \
+            either DATASTORE_MODIFY or DATASTORE_BACKUP and being the owner of the group.",
     },
 )]
 /// Verify backups.
@@ -790,12 +856,13 @@ pub fn verify(
     ignore_verified: Option<bool>,
     outdated_after: Option<i64>,
     max_depth: Option<usize>,
+    repair: bool,
     rpcenv: &mut dyn RpcEnvironment,
 ) -> Result<Value, Error> {
     let auth_id: Authid = rpcenv.get_auth_id().unwrap().parse()?;
     let ns = ns.unwrap_or_default();
 
-    let owner_check_required = check_ns_privs_full(
+    let mut owner_check_required = check_ns_privs_full(
         &store,
         &ns,
         &auth_id,
@@ -803,7 +870,18 @@ pub fn verify(
         PRIV_DATASTORE_BACKUP,
     )?;
 
-    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read))?;
+    // repairing rewrites manifests, so it needs the same privileges as modifying backups
+    if repair {
+        owner_check_required |= check_ns_privs_full(
+            &store,
+            &ns,
+            &auth_id,
+            PRIV_DATASTORE_MODIFY,
+            PRIV_DATASTORE_BACKUP,
+        )?;
+    }
+
+    let datastore = DataStore::lookup_datastore(&store, Some(verify_operation(repair)), None)?;
     let ignore_verified = ignore_verified.unwrap_or(true);
 
     let worker_id;
@@ -848,7 +926,7 @@ pub fn verify(
                 check_backup_owner(&owner, &auth_id)?;
             }
 
-            backup_group = Some(datastore.backup_group(ns.clone(), group));
+            backup_group = Some(datastore.backup_group(ns.clone(), group)?);
             worker_type = "verify_group";
         }
         (None, None, None) => {
@@ -869,6 +947,31 @@ pub fn verify(
         auth_id.to_string(),
         to_stdout,
         move |worker| {
+            if repair {
+                task_log!(worker, "checking for snapshots with missing manifest");
+                if let Some(backup_dir) = &backup_dir {
+                    repair_snapshot_manifest(&worker, &datastore, backup_dir);
+                } else if let Some(backup_group) = &backup_group {
+                    for snapshot in backup_group.iter_snapshots()? {
+                        repair_snapshot_manifest(&worker, &datastore, &snapshot?);
+                    }
+                } else {
+                    for ns in datastore.recursive_iter_backup_ns_ok(ns.clone(), max_depth)? {
+                        for group in datastore.iter_backup_groups_ok(ns)? {
+                            if owner_check_required {
+                                let owner = group.get_owner()?;
+                                if check_backup_owner(&owner, &auth_id).is_err() {
+                                    continue;
+                                }
+                            }
+                            for snapshot in group.iter_snapshots()? {
+                                repair_snapshot_manifest(&worker, &datastore, &snapshot?);
+                            }
+                        }
+                    }
+                }
+            }
+
             let verify_worker = crate::backup::VerifyWorker::new(worker.clone(), datastore);
             let failed_dirs = if let Some(backup_dir) = backup_dir {
                 let mut res = Vec::new();
@@ -984,7 +1087,7 @@ pub fn prune(
     )?;
 
     let worker_id = format!("{}:{}:{}", store, ns, group);
-    let group = datastore.backup_group(ns.clone(), group);
+    let group = datastore.backup_group(ns.clone(), group)?;
 
     #[derive(Debug, serde::Serialize)]
     struct PruneResult {
@@ -1150,7 +1253,7 @@ pub fn prune_datastore(
         true,
     )?;
 
-    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write))?;
+    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write), None)?;
     let ns = prune_options.ns.clone().unwrap_or_default();
     let worker_id = format!("{}:{}", store, ns);
 
@@ -1175,6 +1278,12 @@ pub fn prune_datastore(
             store: {
                 schema: DATASTORE_SCHEMA,
             },
+            "dry-run": {
+                type: bool,
+                optional: true,
+                default: false,
+                description: "Only report what would be removed, without modifying the datastore.",
+            },
         },
     },
     returns: {
@@ -1187,12 +1296,32 @@ pub fn prune_datastore(
 /// Start garbage collection.
 pub fn start_garbage_collection(
     store: String,
+    dry_run: bool,
     _info: &ApiMethod,
     rpcenv: &mut dyn RpcEnvironment,
 ) -> Result<Value, Error> {
-    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write))?;
     let auth_id: Authid = rpcenv.get_auth_id().unwrap().parse()?;
 
+    if dry_run {
+        let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read), None)?;
+        let to_stdout = rpcenv.env_type() == RpcEnvironmentType::CLI;
+
+        let upid_str = WorkerTask::new_thread(
+            "garbage_collection",
+            Some(store),
+            auth_id.to_string(),
+            to_stdout,
+            move |worker| {
+                datastore.garbage_collect_dry_run(&*worker)?;
+                Ok(())
+            },
+        )?;
+
+        return Ok(json!(upid_str));
+    }
+
+    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write), None)?;
+
     let job = Job::new("garbage_collection", &store)
         .map_err(|_| format_err!("garbage collection already running"))?;
 
@@ -1241,7 +1370,7 @@ pub fn garbage_collection_status(
         ..Default::default()
     };
 
-    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read))?;
+    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read), None)?;
     let status_in_memory = datastore.last_gc_status();
     let state_file = JobState::load("garbage_collection", &store)
         .map_err(|err| log::error!("could not open GC statefile for {store}: {err}"))
@@ -1501,7 +1630,7 @@ pub fn download_file_decoded(
                     format_err!("unable to read dynamic index '{:?}' - {}", &path, err)
                 })?;
                 let (csum, size) = index.compute_csum();
-                manifest.verify_file(&file_name, &csum, size)?;
+                manifest.ensure_file(&file_name, &csum, size)?;
 
                 let chunk_reader = LocalChunkReader::new(datastore, None, CryptMode::None);
                 let reader = CachedChunkReader::new(chunk_reader, index, 1).seekable();
@@ -1516,7 +1645,7 @@ pub fn download_file_decoded(
                 })?;
 
                 let (csum, size) = index.compute_csum();
-                manifest.verify_file(&file_name, &csum, size)?;
+                manifest.ensure_file(&file_name, &csum, size)?;
 
                 let chunk_reader = LocalChunkReader::new(datastore, None, CryptMode::None);
                 let reader = CachedChunkReader::new(chunk_reader, index, 1).seekable();
@@ -1702,7 +1831,7 @@ pub async fn catalog(
             .map_err(|err| format_err!("unable to read dynamic index '{:?}' - {}", &path, err))?;
 
         let (csum, size) = index.compute_csum();
-        manifest.verify_file(file_name, &csum, size)?;
+        manifest.ensure_file(file_name, &csum, size)?;
 
         let chunk_reader = LocalChunkReader::new(datastore, None, CryptMode::None);
         let reader = BufferedDynamicReader::new(index, chunk_reader);
@@ -1757,7 +1886,7 @@ fn get_local_pxar_reader(
         .map_err(|err| format_err!("unable to read dynamic index '{:?}' - {}", &path, err))?;
 
     let (csum, size) = index.compute_csum();
-    manifest.verify_file(pxar_name, &csum, size)?;
+    manifest.ensure_file(pxar_name, &csum, size)?;
 
     let chunk_reader = LocalChunkReader::new(datastore, None, CryptMode::None);
     let reader = BufferedDynamicReader::new(index, chunk_reader);
@@ -1900,7 +2029,7 @@ pub fn get_rrd_stats(
     cf: RRDMode,
     _param: Value,
 ) -> Result<Value, Error> {
-    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read))?;
+    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read), None)?;
     let disk_manager = crate::tools::disks::DiskManage::new();
 
     let mut rrd_fields = vec![
@@ -1943,6 +2072,133 @@ pub fn get_active_operations(store: String, _param: Value) -> Result<Value, Erro
     }))
 }
 
+#[api(
+    input: {
+        properties: {
+            store: { schema: DATASTORE_SCHEMA },
+            ns: {
+                type: BackupNamespace,
+                optional: true,
+            },
+            backup_group: {
+                type: pbs_api_types::BackupGroup,
+                flatten: true,
+            },
+        },
+    },
+    returns: {
+        type: GroupStats,
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "Requires on /datastore/{store}[/{namespace}] either DATASTORE_AUDIT for any \
+            or DATASTORE_BACKUP and being the owner of the group",
+    },
+)]
+/// Get aggregated statistics for a backup group
+pub fn get_group_stats(
+    store: String,
+    ns: Option<BackupNamespace>,
+    backup_group: pbs_api_types::BackupGroup,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<GroupStats, Error> {
+    let auth_id: Authid = rpcenv.get_auth_id().unwrap().parse()?;
+    let ns = ns.unwrap_or_default();
+
+    let datastore = check_privs_and_load_store(
+        &store,
+        &ns,
+        &auth_id,
+        PRIV_DATASTORE_AUDIT,
+        PRIV_DATASTORE_BACKUP,
+        Some(Operation::Read),
+        &backup_group,
+    )?;
+
+    let group = datastore.backup_group(ns, backup_group)?;
+    if !group.exists() {
+        bail!("backup group {} does not exist", group.group());
+    }
+
+    datastore.stats_for_group(&group)
+}
+
+#[api(
+    input: {
+        properties: {
+            store: { schema: DATASTORE_SCHEMA },
+            ns: {
+                type: BackupNamespace,
+                optional: true,
+            },
+            "max-depth": {
+                schema: NS_MAX_DEPTH_SCHEMA,
+                optional: true,
+            },
+            "min-size": {
+                type: HumanByte,
+            },
+        },
+    },
+    returns: {
+        description: "List of snapshot files which are at least 'min-size' large.",
+        type: Array,
+        items: { type: SnapshotFileSize },
+    },
+    access: {
+        permission: &Permission::Anybody,
+        description: "Requires on /datastore/{store}[/{namespace}] either DATASTORE_AUDIT for any \
+            or DATASTORE_BACKUP and being the owner of the group",
+    },
+)]
+/// List snapshot files which are at least 'min-size' large, sorted by descending size per
+/// snapshot.
+pub fn list_large_files(
+    store: String,
+    ns: Option<BackupNamespace>,
+    max_depth: Option<usize>,
+    min_size: HumanByte,
+    rpcenv: &mut dyn RpcEnvironment,
+) -> Result<Vec<SnapshotFileSize>, Error> {
+    let auth_id: Authid = rpcenv.get_auth_id().unwrap().parse()?;
+    let ns = ns.unwrap_or_default();
+    let min_size = min_size.as_u64();
+
+    check_ns_privs_full(
+        &store,
+        &ns,
+        &auth_id,
+        PRIV_DATASTORE_AUDIT,
+        PRIV_DATASTORE_BACKUP,
+    )?;
+
+    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Read), None)?;
+
+    let mut list = Vec::new();
+    for group in ListAccessibleBackupGroups::new_with_privs(
+        &datastore,
+        ns,
+        max_depth.unwrap_or(MAX_NAMESPACE_DEPTH),
+        Some(PRIV_DATASTORE_AUDIT), // overrides the owner check
+        Some(PRIV_DATASTORE_BACKUP),
+        Some(&auth_id),
+    )? {
+        let group = group?;
+        for info in group.list_backups()? {
+            for (filename, size) in info.list_large_files(min_size)? {
+                list.push(SnapshotFileSize {
+                    backup: info.backup_dir.dir().clone(),
+                    ns: info.backup_dir.backup_ns().clone(),
+                    filename,
+                    size,
+                });
+            }
+        }
+    }
+
+    Ok(list)
+}
+
 #[api(
     input: {
         properties: {
@@ -2280,9 +2536,9 @@ pub async fn set_backup_owner(
             PRIV_DATASTORE_BACKUP,
         )?;
 
-        let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write))?;
+        let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write), None)?;
 
-        let backup_group = datastore.backup_group(ns, backup_group);
+        let backup_group = datastore.backup_group(ns, backup_group)?;
 
         if owner_check_required {
             let owner = backup_group.get_owner()?;
@@ -2372,12 +2628,20 @@ const DATASTORE_INFO_SUBDIRS: SubdirMap = &[
             .get(&API_METHOD_GET_GROUP_NOTES)
             .put(&API_METHOD_SET_GROUP_NOTES),
     ),
+    (
+        "group-stats",
+        &Router::new().get(&API_METHOD_GET_GROUP_STATS),
+    ),
     (
         "groups",
         &Router::new()
             .get(&API_METHOD_LIST_GROUPS)
             .delete(&API_METHOD_DELETE_GROUP),
     ),
+    (
+        "large-files",
+        &Router::new().get(&API_METHOD_LIST_LARGE_FILES),
+    ),
     (
         "namespace",
         // FIXME: move into datastore:: sub-module?!
@@ -2426,3 +2690,21 @@ const DATASTORE_INFO_ROUTER: Router = Router::new()
 pub const ROUTER: Router = Router::new()
     .get(&API_METHOD_GET_DATASTORE_LIST)
     .match_all("store", &DATASTORE_INFO_ROUTER);
+
+#[cfg(test)]
+mod tests {
+    use pbs_api_types::{MaintenanceMode, MaintenanceType};
+
+    use super::*;
+
+    #[test]
+    fn verify_repair_rejected_in_read_only_mode() {
+        let mode = MaintenanceMode {
+            ty: MaintenanceType::ReadOnly,
+            message: None,
+        };
+
+        assert!(mode.check(Some(verify_operation(false))).is_ok());
+        assert!(mode.check(Some(verify_operation(true))).is_err());
+    }
+}
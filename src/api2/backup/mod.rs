@@ -18,8 +18,9 @@ use proxmox_sortable_macro::sortable;
 
 use pbs_api_types::{
     Authid, BackupNamespace, BackupType, Operation, SnapshotVerifyState, VerifyState,
-    BACKUP_ARCHIVE_NAME_SCHEMA, BACKUP_ID_SCHEMA, BACKUP_NAMESPACE_SCHEMA, BACKUP_TIME_SCHEMA,
-    BACKUP_TYPE_SCHEMA, CHUNK_DIGEST_SCHEMA, DATASTORE_SCHEMA, PRIV_DATASTORE_BACKUP,
+    BACKUP_ARCHIVE_NAME_SCHEMA, BACKUP_ID_MAX_LENGTH, BACKUP_ID_SCHEMA, BACKUP_NAMESPACE_SCHEMA,
+    BACKUP_TIME_SCHEMA, BACKUP_TYPE_SCHEMA, CHUNK_DIGEST_SCHEMA, DATASTORE_SCHEMA,
+    PRIV_DATASTORE_BACKUP,
 };
 use pbs_config::CachedUserInfo;
 use pbs_datastore::index::IndexFile;
@@ -50,6 +51,7 @@ pub const API_METHOD_UPGRADE_BACKUP: ApiMethod = ApiMethod::new(
             ("backup-time", false, &BACKUP_TIME_SCHEMA),
             ("debug", true, &BooleanSchema::new("Enable verbose debug logging.").schema()),
             ("benchmark", true, &BooleanSchema::new("Job is a benchmark (do not keep data).").schema()),
+            ("allow-future-time", true, &BooleanSchema::new("Allow a backup time more than 5 minutes in the future.").schema()),
         ]),
     )
 ).access(
@@ -76,6 +78,7 @@ fn upgrade_to_backup_protocol(
     async move {
         let debug = param["debug"].as_bool().unwrap_or(false);
         let benchmark = param["benchmark"].as_bool().unwrap_or(false);
+        let allow_future_time = param["allow-future-time"].as_bool().unwrap_or(false);
 
         let auth_id: Authid = rpcenv.get_auth_id().unwrap().parse()?;
 
@@ -94,7 +97,7 @@ fn upgrade_to_backup_protocol(
             )
             .map_err(|err| http_err!(FORBIDDEN, "{err}"))?;
 
-        let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write))?;
+        let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write), None)?;
 
         let protocols = parts
             .headers
@@ -122,7 +125,7 @@ fn upgrade_to_backup_protocol(
 
         let env_type = rpcenv.env_type();
 
-        let backup_group = datastore.backup_group(backup_ns, backup_dir_arg.group.clone());
+        let backup_group = datastore.backup_group(backup_ns, backup_dir_arg.group.clone())?;
 
         let worker_type = if backup_group.backup_type() == BackupType::Host
             && backup_group.backup_id() == "benchmark"
@@ -138,6 +141,15 @@ fn upgrade_to_backup_protocol(
             "backup"
         };
 
+        // only new groups are limited, existing ones with longer IDs can still be backed up
+        let backup_id = backup_group.backup_id();
+        if backup_id.len() > BACKUP_ID_MAX_LENGTH && !backup_group.exists() {
+            bail!(
+                "backup id '{backup_id}' is too long ({} > {BACKUP_ID_MAX_LENGTH})",
+                backup_id.len()
+            );
+        }
+
         // lock backup group to only allow one backup per group at a time
         let (owner, _group_guard) = datastore.create_locked_backup_group(
             backup_group.backup_ns(),
@@ -154,7 +166,7 @@ fn upgrade_to_backup_protocol(
         }
 
         let last_backup = {
-            let info = backup_group.last_backup(true).unwrap_or(None);
+            let info = backup_group.last_finished_backup().unwrap_or(None);
             if let Some(info) = info {
                 let (manifest, _) = info.backup_dir.load_manifest()?;
                 let verify = manifest.unprotected["verify_state"].clone();
@@ -191,8 +203,11 @@ fn upgrade_to_backup_protocol(
             None
         };
 
-        let (path, is_new, snap_guard) =
-            datastore.create_locked_backup_dir(backup_dir.backup_ns(), backup_dir.as_ref())?;
+        let (path, is_new, snap_guard) = datastore.create_locked_backup_dir(
+            backup_dir.backup_ns(),
+            backup_dir.as_ref(),
+            allow_future_time,
+        )?;
         if !is_new {
             bail!("backup directory already exists.");
         }
@@ -180,6 +180,7 @@ pub fn get_values(
 
     for ty in [
         "acme",
+        "datastore-capacity",
         "gc",
         "package-updates",
         "prune",
@@ -108,6 +108,8 @@ pub fn create_prune_job(
 
     user_info.check_privs(&auth_id, &config.acl_path(), PRIV_DATASTORE_MODIFY, true)?;
 
+    config.options.validate()?;
+
     do_create_prune_job(config, None)
 }
 
@@ -318,6 +320,8 @@ pub fn update_prune_job(
         data.options.keep.keep_yearly = Some(value);
     }
 
+    data.options.validate()?;
+
     config.set_data(&id, "prune", &data)?;
 
     prune::save_config(&config)?;
@@ -8,13 +8,16 @@ use proxmox_router::SubdirMap;
 use proxmox_sortable_macro::sortable;
 use serde_json::Value;
 
-use proxmox_router::{http_bail, http_err, ApiMethod, Permission, Router, RpcEnvironment};
+use proxmox_router::{
+    http_bail, http_err, ApiMethod, HttpError, Permission, Router, RpcEnvironment,
+};
 use proxmox_schema::{api, param_bail};
 
 use pbs_api_types::{
     Authid, DataStoreListItem, GroupListItem, RateLimitConfig, Remote, RemoteConfig,
-    RemoteConfigUpdater, RemoteWithoutPassword, SyncJobConfig, DATASTORE_SCHEMA, PRIV_REMOTE_AUDIT,
-    PRIV_REMOTE_MODIFY, PROXMOX_CONFIG_DIGEST_SCHEMA, REMOTE_ID_SCHEMA, REMOTE_PASSWORD_SCHEMA,
+    RemoteConfigUpdater, RemoteConnectionStatus, RemoteWithoutPassword, SyncJobConfig,
+    DATASTORE_SCHEMA, PRIV_REMOTE_AUDIT, PRIV_REMOTE_MODIFY, PROXMOX_CONFIG_DIGEST_SCHEMA,
+    REMOTE_ID_SCHEMA, REMOTE_PASSWORD_SCHEMA,
 };
 use pbs_client::{HttpClient, HttpClientOptions};
 use pbs_config::sync;
@@ -344,6 +347,57 @@ pub async fn remote_client(
     Ok(client)
 }
 
+/// Try to connect and authenticate to a remote, and query its version.
+pub async fn test_connection(remote: &Remote) -> Result<RemoteConnectionStatus, Error> {
+    let mut status = RemoteConnectionStatus {
+        reachable: false,
+        auth_ok: false,
+        version: None,
+        latency_ms: None,
+    };
+
+    let client = remote_client_config(remote, None)?;
+
+    if let Err(err) = client.login().await {
+        // got a HTTP response, so the host is reachable but refused our credentials
+        status.reachable = err.downcast_ref::<HttpError>().is_some();
+        return Ok(status);
+    }
+    status.reachable = true;
+    status.auth_ok = true;
+
+    let start = std::time::Instant::now();
+    let api_res = client.get("api2/json/version", None).await?;
+    status.latency_ms = Some(start.elapsed().as_millis() as u64);
+    status.version = api_res["data"]["version"].as_str().map(String::from);
+
+    Ok(status)
+}
+
+#[api(
+    protected: true,
+    input: {
+        properties: {
+            name: {
+                schema: REMOTE_ID_SCHEMA,
+            },
+        },
+    },
+    access: {
+        permission: &Permission::Privilege(&["remote", "{name}"], PRIV_REMOTE_AUDIT, false),
+    },
+    returns: {
+        type: RemoteConnectionStatus,
+    },
+)]
+/// Test the connection and credentials of a remote.cfg entry
+pub async fn test_remote_connection(name: String) -> Result<RemoteConnectionStatus, Error> {
+    let (remote_config, _digest) = pbs_config::remote::config()?;
+    let remote: Remote = remote_config.lookup("remote", &name)?;
+
+    test_connection(&remote).await
+}
+
 #[api(
     input: {
         properties: {
@@ -529,7 +583,13 @@ const ITEM_ROUTER: Router = Router::new()
     .get(&API_METHOD_READ_REMOTE)
     .put(&API_METHOD_UPDATE_REMOTE)
     .delete(&API_METHOD_DELETE_REMOTE)
-    .subdirs(&[("scan", &SCAN_ROUTER)]);
+    .subdirs(&[
+        ("scan", &SCAN_ROUTER),
+        (
+            "test",
+            &Router::new().post(&API_METHOD_TEST_REMOTE_CONNECTION),
+        ),
+    ]);
 
 pub const ROUTER: Router = Router::new()
     .get(&API_METHOD_LIST_REMOTES)
@@ -2,7 +2,7 @@ use anyhow::{bail, format_err, Error};
 use hex::FromHex;
 use serde_json::Value;
 
-use proxmox_router::{http_bail, ApiMethod, Permission, Router, RpcEnvironment};
+use proxmox_router::{http_bail, http_err, ApiMethod, Permission, Router, RpcEnvironment};
 use proxmox_schema::{api, param_bail};
 
 use pbs_api_types::{
@@ -13,7 +13,7 @@ use pbs_api_types::{
 use pbs_config::CachedUserInfo;
 
 use pbs_config::open_backup_lockfile;
-use pbs_key_config::KeyConfig;
+use pbs_key_config::{KeyConfig, KeyConfigError};
 
 use crate::tape::encryption_keys::{
     insert_key, load_key_configs, load_keys, save_key_configs, save_keys, TAPE_KEYS_LOCKFILE,
@@ -142,7 +142,12 @@ pub fn change_passphrase(
             format_err!("password is not allowed when using force")
         ),
         (false, None) => param_bail!("password", format_err!("missing parameter: password")),
-        (false, Some(pass)) => key_config.decrypt(&|| Ok(pass.as_bytes().to_vec()))?,
+        (false, Some(pass)) => key_config
+            .decrypt(&|| Ok(pass.as_bytes().to_vec()))
+            .map_err(|err| match err.downcast_ref::<KeyConfigError>() {
+                Some(KeyConfigError::WrongPassword { .. }) => http_err!(FORBIDDEN, "{err}"),
+                None => err,
+            })?,
         (true, None) => {
             let key = load_keys()?
                 .0
@@ -147,6 +147,8 @@ fn reboot_or_shutdown(command: NodePowerCommand) -> Result<(), Error> {
         NodePowerCommand::Shutdown => "poweroff",
     };
 
+    log::info!("Executing power command: {command}");
+
     let output = Command::new("systemctl")
         .arg(systemctl_command)
         .output()
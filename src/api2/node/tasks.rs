@@ -17,7 +17,7 @@ use proxmox_schema::{api, BooleanSchema, IntegerSchema, ObjectSchema, Schema};
 use proxmox_sortable_macro::sortable;
 
 use pbs_api_types::{
-    Authid, TaskListItem, TaskStateType, Tokenname, Userid, DATASTORE_SCHEMA, NODE_SCHEMA,
+    Authid, TaskStateType, TaskSummary, Tokenname, Userid, DATASTORE_SCHEMA, NODE_SCHEMA,
     PRIV_DATASTORE_MODIFY, PRIV_DATASTORE_VERIFY, PRIV_SYS_AUDIT, PRIV_SYS_MODIFY,
     SYNC_JOB_WORKER_ID_REGEX, UPID, UPID_SCHEMA, VERIFICATION_JOB_WORKER_ID_REGEX,
 };
@@ -197,6 +197,17 @@ fn into_task_list_item(info: proxmox_rest_server::TaskListInfo) -> pbs_api_types
     }
 }
 
+fn into_task_summary(info: proxmox_rest_server::TaskListInfo) -> TaskSummary {
+    let state = info.state.as_ref().map(tasktype);
+    let item = into_task_list_item(info);
+    let duration = item.endtime.map(|endtime| endtime - item.starttime);
+    TaskSummary {
+        item,
+        state,
+        duration,
+    }
+}
+
 #[api(
     input: {
         properties: {
@@ -529,7 +540,7 @@ pub fn list_tasks(
     statusfilter: Option<Vec<TaskStateType>>,
     param: Value,
     rpcenv: &mut dyn RpcEnvironment,
-) -> Result<Vec<TaskListItem>, Error> {
+) -> Result<Vec<TaskSummary>, Error> {
     let auth_id: Authid = rpcenv.get_auth_id().unwrap().parse()?;
     let user_info = CachedUserInfo::new()?;
     let user_privs = user_info.lookup_privs(&auth_id, &["system", "tasks"]);
@@ -546,7 +557,7 @@ pub fn list_tasks(
     };
 
     let mut skipped = 0;
-    let mut result: Vec<TaskListItem> = Vec::new();
+    let mut result: Vec<TaskSummary> = Vec::new();
 
     for info in list {
         let info = match info {
@@ -611,7 +622,7 @@ pub fn list_tasks(
             continue;
         }
 
-        result.push(into_task_list_item(info));
+        result.push(into_task_summary(info));
 
         if result.len() >= limit {
             break;
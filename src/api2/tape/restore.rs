@@ -142,10 +142,14 @@ impl TryFrom<String> for DataStoreMap {
             if let Some(index) = store.find('=') {
                 let mut target = store.split_off(index);
                 target.remove(0); // remove '='
-                let datastore = DataStore::lookup_datastore(&target, Some(Operation::Write))?;
+                let datastore = DataStore::lookup_datastore(&target, Some(Operation::Write), None)?;
                 map.insert(store, datastore);
             } else if default.is_none() {
-                default = Some(DataStore::lookup_datastore(&store, Some(Operation::Write))?);
+                default = Some(DataStore::lookup_datastore(
+                    &store,
+                    Some(Operation::Write),
+                    None,
+                )?);
             } else {
                 bail!("multiple default stores given");
             }
@@ -878,7 +882,7 @@ fn restore_list_worker(
                         }
 
                         let (_rel_path, is_new, _snap_lock) =
-                            datastore.create_locked_backup_dir(&ns, backup_dir.as_ref())?;
+                            datastore.create_locked_backup_dir(&ns, backup_dir.as_ref(), true)?;
 
                         if !is_new {
                             bail!("snapshot {}/{} already exists", datastore.name(), &snapshot);
@@ -1096,9 +1100,9 @@ fn restore_snapshots_to_tmpdir(
 
                 for item in manifest.files() {
                     let mut archive_path = tmp_path.to_owned();
-                    archive_path.push(&item.filename);
+                    archive_path.push(item.filename());
 
-                    let index: Box<dyn IndexFile> = match archive_type(&item.filename)? {
+                    let index: Box<dyn IndexFile> = match archive_type(item.filename())? {
                         ArchiveType::DynamicIndex => {
                             Box::new(DynamicIndexReader::open(&archive_path)?)
                         }
@@ -1415,8 +1419,11 @@ fn restore_archive<'a>(
                         );
                     }
 
-                    let (rel_path, is_new, _snap_lock) =
-                        datastore.create_locked_backup_dir(&backup_ns, backup_dir.as_ref())?;
+                    let (rel_path, is_new, _snap_lock) = datastore.create_locked_backup_dir(
+                        &backup_ns,
+                        backup_dir.as_ref(),
+                        true,
+                    )?;
                     let mut path = datastore.base_path();
                     path.push(rel_path);
 
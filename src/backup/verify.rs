@@ -12,7 +12,7 @@ use pbs_api_types::{
     print_ns_and_snapshot, print_store_and_ns, Authid, BackupNamespace, BackupType, CryptMode,
     SnapshotVerifyState, VerifyState, PRIV_DATASTORE_BACKUP, PRIV_DATASTORE_VERIFY, UPID,
 };
-use pbs_datastore::backup_info::{BackupDir, BackupGroup, BackupInfo};
+use pbs_datastore::backup_info::{BackupDir, BackupGroup, BackupInfo, BackupSortField};
 use pbs_datastore::index::IndexFile;
 use pbs_datastore::manifest::{archive_type, ArchiveType, BackupManifest, FileInfo};
 use pbs_datastore::{DataBlob, DataStore, StoreProgress};
@@ -46,15 +46,15 @@ impl VerifyWorker {
 }
 
 fn verify_blob(backup_dir: &BackupDir, info: &FileInfo) -> Result<(), Error> {
-    let blob = backup_dir.load_blob(&info.filename)?;
+    let blob = backup_dir.load_blob(info.filename())?;
 
     let raw_size = blob.raw_size();
-    if raw_size != info.size {
-        bail!("wrong size ({} != {})", info.size, raw_size);
+    if raw_size != info.size() {
+        bail!("wrong size ({} != {})", info.size(), raw_size);
     }
 
     let csum = openssl::sha::sha256(blob.raw_data());
-    if csum != info.csum {
+    if &csum != info.csum() {
         bail!("wrong index checksum");
     }
 
@@ -275,16 +275,16 @@ fn verify_fixed_index(
     info: &FileInfo,
 ) -> Result<(), Error> {
     let mut path = backup_dir.relative_path();
-    path.push(&info.filename);
+    path.push(info.filename());
 
     let index = verify_worker.datastore.open_fixed_reader(&path)?;
 
     let (csum, size) = index.compute_csum();
-    if size != info.size {
-        bail!("wrong size ({} != {})", info.size, size);
+    if size != info.size() {
+        bail!("wrong size ({} != {})", info.size(), size);
     }
 
-    if csum != info.csum {
+    if &csum != info.csum() {
         bail!("wrong index checksum");
     }
 
@@ -297,16 +297,16 @@ fn verify_dynamic_index(
     info: &FileInfo,
 ) -> Result<(), Error> {
     let mut path = backup_dir.relative_path();
-    path.push(&info.filename);
+    path.push(info.filename());
 
     let index = verify_worker.datastore.open_dynamic_reader(&path)?;
 
     let (csum, size) = index.compute_csum();
-    if size != info.size {
-        bail!("wrong size ({} != {})", info.size, size);
+    if size != info.size() {
+        bail!("wrong size ({} != {})", info.size(), size);
     }
 
-    if csum != info.csum {
+    if &csum != info.csum() {
         bail!("wrong index checksum");
     }
 
@@ -406,8 +406,8 @@ pub fn verify_backup_dir_with_lock(
     let mut verify_result = VerifyState::Ok;
     for info in manifest.files() {
         let result = proxmox_lang::try_block!({
-            task_log!(verify_worker.worker, "  check {}", info.filename);
-            match archive_type(&info.filename)? {
+            task_log!(verify_worker.worker, "  check {}", info.filename());
+            match archive_type(info.filename())? {
                 ArchiveType::FixedIndex => verify_fixed_index(verify_worker, backup_dir, info),
                 ArchiveType::DynamicIndex => verify_dynamic_index(verify_worker, backup_dir, info),
                 ArchiveType::Blob => verify_blob(backup_dir, info),
@@ -423,7 +423,7 @@ pub fn verify_backup_dir_with_lock(
                 "verify {}:{}/{} failed: {}",
                 verify_worker.datastore.name(),
                 backup_dir.dir(),
-                info.filename,
+                info.filename(),
                 err,
             );
             error_count += 1;
@@ -485,7 +485,7 @@ pub fn verify_backup_group(
 
     progress.group_snapshots = snapshot_count as u64;
 
-    BackupInfo::sort_list(&mut list, false); // newest first
+    BackupInfo::sort_list(&mut list, BackupSortField::Time, false); // newest first
     for (pos, info) in list.into_iter().enumerate() {
         if !verify_backup_dir(verify_worker, &info.backup_dir, upid.clone(), filter)? {
             errors.push(print_ns_and_snapshot(
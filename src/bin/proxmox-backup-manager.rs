@@ -10,11 +10,13 @@ use proxmox_schema::api;
 use proxmox_sys::fs::CreateOptions;
 
 use pbs_api_types::percent_encoding::percent_encode_component;
+use proxmox_human_byte::HumanByte;
+
 use pbs_api_types::{
-    BackupNamespace, GroupFilter, RateLimitConfig, SyncJobConfig, DATASTORE_SCHEMA,
-    GROUP_FILTER_LIST_SCHEMA, IGNORE_VERIFIED_BACKUPS_SCHEMA, NS_MAX_DEPTH_SCHEMA,
-    REMOTE_ID_SCHEMA, REMOVE_VANISHED_BACKUPS_SCHEMA, TRANSFER_LAST_SCHEMA, UPID_SCHEMA,
-    VERIFICATION_OUTDATED_AFTER_SCHEMA,
+    print_ns_and_snapshot, BackupNamespace, GroupFilter, RateLimitConfig, SnapshotFileSize,
+    SyncJobConfig, DATASTORE_SCHEMA, GROUP_FILTER_LIST_SCHEMA, IGNORE_VERIFIED_BACKUPS_SCHEMA,
+    NS_MAX_DEPTH_SCHEMA, REMOTE_ID_SCHEMA, REMOVE_VANISHED_BACKUPS_SCHEMA, TRANSFER_LAST_SCHEMA,
+    UPID_SCHEMA, VERIFICATION_OUTDATED_AFTER_SCHEMA,
 };
 use pbs_client::{display_task_log, view_task_result};
 use pbs_config::sync;
@@ -35,6 +37,12 @@ use proxmox_backup_manager::*;
             store: {
                 schema: DATASTORE_SCHEMA,
             },
+            "dry-run": {
+                type: bool,
+                optional: true,
+                default: false,
+                description: "Only report what would be removed, without modifying the datastore.",
+            },
             "output-format": {
                 schema: OUTPUT_FORMAT,
                 optional: true,
@@ -52,7 +60,12 @@ async fn start_garbage_collection(param: Value) -> Result<Value, Error> {
 
     let path = format!("api2/json/admin/datastore/{}/gc", store);
 
-    let result = client.post(&path, None).await?;
+    let args = match param["dry-run"].as_bool() {
+        Some(true) => Some(json!({ "dry-run": true })),
+        _ => None,
+    };
+
+    let result = client.post(&path, args).await?;
 
     view_task_result(&client, result, &output_format).await?;
 
@@ -418,6 +431,12 @@ async fn pull_datastore(
                 schema: VERIFICATION_OUTDATED_AFTER_SCHEMA,
                 optional: true,
             },
+            repair: {
+                type: bool,
+                default: false,
+                optional: true,
+                description: "Try to reconstruct missing manifests before verification.",
+            },
             "output-format": {
                 schema: OUTPUT_FORMAT,
                 optional: true,
@@ -442,6 +461,60 @@ async fn verify(store: String, mut param: Value) -> Result<Value, Error> {
     Ok(Value::Null)
 }
 
+#[api(
+   input: {
+        properties: {
+            store: {
+                schema: DATASTORE_SCHEMA,
+            },
+            ns: {
+                type: BackupNamespace,
+                optional: true,
+            },
+            "max-depth": {
+                schema: NS_MAX_DEPTH_SCHEMA,
+                optional: true,
+            },
+            "min-size": {
+                type: HumanByte,
+            },
+            "output-format": {
+                schema: OUTPUT_FORMAT,
+                optional: true,
+            },
+        }
+   }
+)]
+/// List snapshot files which are at least 'min-size' large.
+async fn check_quota(store: String, mut param: Value) -> Result<Value, Error> {
+    let output_format = extract_output_format(&mut param);
+
+    let client = connect_to_localhost()?;
+
+    let path = format!("api2/json/admin/datastore/{}/large-files", store);
+
+    let mut result = client.get(&path, Some(param)).await?;
+    let mut data = result["data"].take();
+
+    if output_format == "text" {
+        let list: Vec<SnapshotFileSize> = serde_json::from_value(data)?;
+        for item in list {
+            let snapshot = print_ns_and_snapshot(&item.ns, &item.backup);
+            println!(
+                "{snapshot}/{}: {}",
+                item.filename,
+                HumanByte::from(item.size)
+            );
+        }
+    } else {
+        let return_type = &api2::admin::datastore::API_METHOD_LIST_LARGE_FILES.returns;
+        let options = default_table_format_options();
+        format_and_print_result_full(&mut data, return_type, &output_format, &options);
+    }
+
+    Ok(Value::Null)
+}
+
 #[api()]
 /// System report
 async fn report() -> Result<Value, Error> {
@@ -533,6 +606,13 @@ async fn run() -> Result<(), Error> {
                 .arg_param(&["store"])
                 .completion_cb("store", pbs_config::datastore::complete_datastore_name),
         )
+        .insert(
+            "check-quota",
+            CliCommand::new(&API_METHOD_CHECK_QUOTA)
+                .arg_param(&["store"])
+                .completion_cb("store", pbs_config::datastore::complete_datastore_name)
+                .completion_cb("ns", complete_sync_local_datastore_namespace),
+        )
         .insert("report", CliCommand::new(&API_METHOD_REPORT))
         .insert("versions", CliCommand::new(&API_METHOD_GET_VERSIONS));
 
@@ -749,7 +829,8 @@ pub fn complete_remote_datastore_group(_arg: &str, param: &HashMap<String, Strin
         Some((None, source_store)) => {
             let mut rpcenv = CliEnvironment::new();
             rpcenv.set_auth_id(Some(String::from("root@pam")));
-            crate::api2::admin::datastore::list_groups(source_store, ns, &mut rpcenv).ok()
+            crate::api2::admin::datastore::list_groups(source_store, ns, None, None, &mut rpcenv)
+                .ok()
         }
         _ => None,
     } {
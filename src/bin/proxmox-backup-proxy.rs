@@ -374,6 +374,7 @@ async fn run() -> Result<(), Error> {
     start_task_scheduler();
     start_stat_generator();
     start_traffic_control_updater();
+    start_manifest_lock_cleanup();
 
     server.await?;
     log::info!("server shutting down, waiting for active workers to complete");
@@ -426,6 +427,46 @@ fn start_traffic_control_updater() {
     tokio::spawn(task.map(|_| ()));
 }
 
+// Removes manifest lock files left over from a crash, runs once on startup.
+fn start_manifest_lock_cleanup() {
+    tokio::task::spawn_blocking(|| {
+        let config = match pbs_config::datastore::config() {
+            Err(err) => {
+                eprintln!("unable to read datastore config - {err}");
+                return;
+            }
+            Ok((config, _digest)) => config,
+        };
+
+        let datastore_list: Vec<DataStoreConfig> = config
+            .convert_to_typed_array("datastore")
+            .unwrap_or_default();
+
+        for store_config in datastore_list {
+            // the snapshots of an offline or unmounted datastore are not visible, so all of its
+            // lock files would look orphaned
+            if store_config.get_maintenance_mode().is_some() {
+                continue;
+            }
+            let store = &store_config.name;
+
+            let datastore = match DataStore::lookup_datastore(store, Some(Operation::Read), None) {
+                Ok(datastore) => datastore,
+                Err(err) => {
+                    eprintln!("lookup_datastore failed - {err}");
+                    continue;
+                }
+            };
+
+            match datastore.cleanup_manifest_lock_files() {
+                Ok(0) => (),
+                Ok(count) => log::info!("removed {count} orphaned manifest locks of '{store}'"),
+                Err(err) => log::error!("manifest lock cleanup on '{store}' failed - {err}"),
+            }
+        }
+    });
+}
+
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 fn next_minute() -> Instant {
@@ -465,6 +506,7 @@ async fn schedule_tasks() -> Result<(), Error> {
     schedule_datastore_verify_jobs().await;
     schedule_tape_backup_jobs().await;
     schedule_task_log_rotate().await;
+    schedule_datastore_capacity_check().await;
 
     Ok(())
 }
@@ -502,7 +544,8 @@ async fn schedule_datastore_garbage_collection() {
 
         {
             // limit datastore scope due to Op::Lookup
-            let datastore = match DataStore::lookup_datastore(&store, Some(Operation::Lookup)) {
+            let datastore = match DataStore::lookup_datastore(&store, Some(Operation::Lookup), None)
+            {
                 Ok(datastore) => datastore,
                 Err(err) => {
                     eprintln!("lookup_datastore failed - {err}");
@@ -545,7 +588,7 @@ async fn schedule_datastore_garbage_collection() {
             Err(_) => continue, // could not get lock
         };
 
-        let datastore = match DataStore::lookup_datastore(&store, Some(Operation::Write)) {
+        let datastore = match DataStore::lookup_datastore(&store, Some(Operation::Write), None) {
             Ok(datastore) => datastore,
             Err(err) => {
                 log::warn!("skipping scheduled GC on {store}, could look it up - {err}");
@@ -723,6 +766,26 @@ async fn schedule_tape_backup_jobs() {
     }
 }
 
+async fn schedule_datastore_capacity_check() {
+    // check once after startup and then once a day, to not send the same warning over and over
+    static LAST_CHECK: Mutex<Option<Instant>> = Mutex::new(None);
+
+    {
+        let mut last_check = LAST_CHECK.lock().unwrap();
+        if matches!(*last_check, Some(last) if last.elapsed() < Duration::from_secs(24 * 3600)) {
+            return;
+        }
+        *last_check = Some(Instant::now());
+    }
+
+    let result = tokio::task::spawn_blocking(server::check_datastore_capacity).await;
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => eprintln!("datastore capacity check failed - {err}"),
+        Err(err) => eprintln!("datastore capacity check panicked - {err}"),
+    }
+}
+
 async fn schedule_task_log_rotate() {
     let worker_type = "logrotate";
     let job_id = "access-log_and_task-archive";
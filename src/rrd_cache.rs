@@ -83,13 +83,14 @@ pub fn extract_rrd_data(
 ) -> Result<Option<proxmox_rrd::Entry>, Error> {
     let end = proxmox_time::epoch_f64() as u64;
 
-    let (start, resolution) = match timeframe {
-        RRDTimeFrame::Hour => (end - 3600, 60),
-        RRDTimeFrame::Day => (end - 3600 * 24, 60),
-        RRDTimeFrame::Week => (end - 3600 * 24 * 7, 30 * 60),
-        RRDTimeFrame::Month => (end - 3600 * 24 * 30, 30 * 60),
-        RRDTimeFrame::Year => (end - 3600 * 24 * 365, 6 * 60 * 60),
-        RRDTimeFrame::Decade => (end - 10 * 3600 * 24 * 366, 7 * 86400),
+    let start = end - timeframe.duration_secs();
+    let resolution = match timeframe {
+        RRDTimeFrame::Hour => 60,
+        RRDTimeFrame::Day => 60,
+        RRDTimeFrame::Week => 30 * 60,
+        RRDTimeFrame::Month => 30 * 60,
+        RRDTimeFrame::Year => 6 * 60 * 60,
+        RRDTimeFrame::Decade => 7 * 86400,
     };
 
     let cf = match mode {
@@ -0,0 +1,82 @@
+use anyhow::Error;
+
+use pbs_api_types::{DataStoreConfig, Operation, RRDMode, RRDTimeFrame};
+use pbs_datastore::DataStore;
+
+use crate::rrd_cache::extract_rrd_data;
+use crate::server::send_datastore_capacity_alert;
+use crate::tools::statistics::linear_regression;
+
+/// Usage in percent from which on a datastore is considered to be running full.
+pub const DATASTORE_CAPACITY_ALERT_THRESHOLD: f64 = 90.0;
+
+/// Average growth of the used space of a datastore in bytes per day over the last month.
+///
+/// Derived from the RRD statistics, `None` if there is not enough data.
+fn datastore_growth_per_day(store: &str) -> Result<Option<f64>, Error> {
+    let rrd_dir = format!("datastore/{store}");
+    let used = match extract_rrd_data(&rrd_dir, "used", RRDTimeFrame::Month, RRDMode::Average)? {
+        Some(used) => used,
+        None => return Ok(None),
+    };
+
+    let mut time_list = Vec::new();
+    let mut used_list = Vec::new();
+    for (idx, value) in used.data.iter().enumerate() {
+        if let Some(value) = value {
+            time_list.push(used.start + (idx as u64) * used.resolution);
+            used_list.push(*value);
+        }
+    }
+
+    // same minimum as for the estimated full date in the datastore status
+    if used_list.len() < 7 {
+        return Ok(None);
+    }
+
+    Ok(linear_regression(&time_list, &used_list).map(|(_, per_second)| per_second * 86400.0))
+}
+
+/// Checks all configured datastores and sends a notification for those running full.
+pub fn check_datastore_capacity() -> Result<(), Error> {
+    let (config, _digest) = pbs_config::datastore::config()?;
+    let datastore_list: Vec<DataStoreConfig> = config.convert_to_typed_array("datastore")?;
+
+    for store_config in datastore_list {
+        // an offline datastore's path may not be mounted, we'd check the parent file system
+        if store_config
+            .get_maintenance_mode()
+            .map_or(false, |mode| mode.check(Some(Operation::Read)).is_err())
+        {
+            continue;
+        }
+        let store = &store_config.name;
+
+        let datastore = match DataStore::lookup_datastore(store, Some(Operation::Read), None) {
+            Ok(datastore) => datastore,
+            Err(err) => {
+                log::warn!("capacity check: lookup of datastore '{store}' failed - {err}");
+                continue;
+            }
+        };
+
+        let growth_per_day = datastore_growth_per_day(store).unwrap_or_else(|err| {
+            log::warn!("capacity check: could not read statistics of '{store}' - {err}");
+            None
+        });
+
+        match datastore.capacity_alert(DATASTORE_CAPACITY_ALERT_THRESHOLD, growth_per_day) {
+            Ok(Some(alert)) => {
+                if let Err(err) = send_datastore_capacity_alert(store, &alert) {
+                    log::error!(
+                        "capacity check: sending notification for '{store}' failed - {err}"
+                    );
+                }
+            }
+            Ok(None) => (),
+            Err(err) => log::warn!("capacity check on datastore '{store}' failed - {err}"),
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use anyhow::Error;
 use std::sync::Arc;
+use std::time::Duration;
 
 use proxmox_sys::task_log;
 
@@ -9,6 +10,9 @@ use proxmox_rest_server::WorkerTask;
 
 use crate::server::{jobstate::Job, send_gc_status};
 
+/// Interval for logging progress during the GC mark phase.
+const GC_PROGRESS_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Runs a garbage collection job.
 pub fn do_garbage_collection_job(
     mut job: Job,
@@ -33,7 +37,8 @@ pub fn do_garbage_collection_job(
                 task_log!(worker, "task triggered by schedule '{event_str}'");
             }
 
-            let result = datastore.garbage_collection(&*worker, worker.upid());
+            let result =
+                datastore.garbage_collection(&*worker, worker.upid(), Some(GC_PROGRESS_INTERVAL));
 
             let status = worker.create_state(&result);
 
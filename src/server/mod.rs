@@ -25,6 +25,9 @@ pub use gc_job::*;
 mod realm_sync_job;
 pub use realm_sync_job::*;
 
+mod capacity_check;
+pub use capacity_check::*;
+
 pub mod notifications;
 pub use notifications::*;
 
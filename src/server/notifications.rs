@@ -16,6 +16,7 @@ use pbs_api_types::{
     APTUpdateInfo, DataStoreConfig, DatastoreNotify, GarbageCollectionStatus, NotificationMode,
     Notify, SyncJobConfig, TapeBackupJobSetup, User, Userid, VerificationJobConfig,
 };
+use pbs_datastore::StorageAlert;
 use proxmox_notify::endpoints::sendmail::{SendmailConfig, SendmailEndpoint};
 use proxmox_notify::{Endpoint, Notification, Severity};
 
@@ -200,6 +201,47 @@ pub fn send_gc_status(
     Ok(())
 }
 
+/// Send a warning that a datastore is running full.
+pub fn send_datastore_capacity_alert(datastore: &str, alert: &StorageAlert) -> Result<(), Error> {
+    let (fqdn, port) = get_server_url();
+    let hostname = proxmox_sys::nodename().to_string();
+
+    let data = json!({
+        "datastore": datastore,
+        "fqdn": fqdn,
+        "hostname": &hostname,
+        "port": port,
+        "used-percent": format!("{:.1}", alert.used_percent),
+        "free-bytes": alert.free_bytes,
+        "days-until-full": alert
+            .estimated_days_until_full
+            .map(|days| format!("{:.0}", days.ceil())),
+    });
+
+    let metadata = HashMap::from([
+        ("datastore".into(), datastore.into()),
+        ("hostname".into(), hostname),
+        ("type".into(), "datastore-capacity".into()),
+    ]);
+
+    let notification =
+        Notification::from_template(Severity::Warning, "datastore-capacity", data, metadata);
+
+    let (email, _notify, mode) = lookup_datastore_notify_settings(datastore);
+    match mode {
+        NotificationMode::LegacySendmail => {
+            if let Some(email) = email {
+                send_sendmail_legacy_notification(notification, &email)?;
+            }
+        }
+        NotificationMode::NotificationSystem => {
+            send_notification(notification)?;
+        }
+    }
+
+    Ok(())
+}
+
 pub fn send_verify_status(
     job: VerificationJobConfig,
     result: &Result<Vec<String>, Error>,
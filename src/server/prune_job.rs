@@ -2,6 +2,8 @@ use std::sync::Arc;
 
 use anyhow::Error;
 
+use proxmox_human_byte::HumanByte;
+use proxmox_sys::fs::lock_dir_noblock;
 use proxmox_sys::{task_log, task_warn};
 
 use pbs_api_types::{
@@ -46,6 +48,10 @@ pub fn prune_datastore(
         task_log!(worker, "retention options: {rendered_options}");
     }
 
+    let mut freed_bytes = 0;
+    let mut removed_snapshots = 0;
+    let mut skipped_groups = 0;
+
     for group in ListAccessibleBackupGroups::new_with_privs(
         &datastore,
         ns,
@@ -56,6 +62,30 @@ pub fn prune_datastore(
     )? {
         let group = group?;
         let ns = group.backup_ns();
+
+        // lock the group so that no backup can add a new snapshot while we prune it
+        let _group_guard = if dry_run {
+            None
+        } else {
+            match lock_dir_noblock(
+                &group.full_group_path(),
+                "backup group",
+                "group is in use by a running backup or another task",
+            ) {
+                Ok(guard) => Some(guard),
+                Err(err) => {
+                    skipped_groups += 1;
+                    task_warn!(
+                        worker,
+                        "skipping group {ns}:\"{}/{}\" - {err}",
+                        group.backup_type(),
+                        group.backup_id()
+                    );
+                    continue;
+                }
+            }
+        };
+
         let list = group.list_backups()?;
 
         let mut prune_info = compute_prune_info(list, &prune_options.keep)?;
@@ -79,15 +109,46 @@ pub fn prune_datastore(
                 group.backup_id(),
                 info.backup_dir.backup_time_string()
             );
-            if !keep && !dry_run {
-                if let Err(err) = datastore.remove_backup_dir(ns, info.backup_dir.as_ref(), false) {
+            if keep {
+                continue;
+            }
+
+            let size = if dry_run {
+                info.total_file_size().unwrap_or_else(|err| {
                     let path = info.backup_dir.relative_path();
-                    task_warn!(worker, "failed to remove dir {path:?}: {err}");
+                    task_warn!(worker, "failed to compute size of {path:?}: {err}");
+                    0
+                })
+            } else {
+                match datastore.remove_backup_dir(ns, info.backup_dir.as_ref(), false) {
+                    Ok(size) => size,
+                    Err(err) => {
+                        let path = info.backup_dir.relative_path();
+                        task_warn!(worker, "failed to remove dir {path:?}: {err}");
+                        continue;
+                    }
                 }
-            }
+            };
+
+            freed_bytes += size;
+            removed_snapshots += 1;
         }
     }
 
+    task_log!(
+        worker,
+        "{} {} across {removed_snapshots} snapshots",
+        if dry_run { "Would free" } else { "Freed" },
+        HumanByte::from(freed_bytes),
+    );
+
+    if skipped_groups > 0 {
+        task_warn!(
+            worker,
+            "skipped {skipped_groups} busy group(s), they were not pruned"
+        );
+    }
+
     Ok(())
 }
 
@@ -132,7 +193,7 @@ pub fn do_prune_job(
     auth_id: &Authid,
     schedule: Option<String>,
 ) -> Result<String, Error> {
-    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write))?;
+    let datastore = DataStore::lookup_datastore(&store, Some(Operation::Write), None)?;
 
     let worker_type = job.jobtype().to_string();
     let auth_id = auth_id.clone();
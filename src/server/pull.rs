@@ -27,7 +27,8 @@ use pbs_datastore::dynamic_index::DynamicIndexReader;
 use pbs_datastore::fixed_index::FixedIndexReader;
 use pbs_datastore::index::IndexFile;
 use pbs_datastore::manifest::{
-    archive_type, ArchiveType, BackupManifest, FileInfo, CLIENT_LOG_BLOB_NAME, MANIFEST_BLOB_NAME,
+    archive_type, ArchiveType, BackupManifest, FileInfo, VerifyFileResult, CLIENT_LOG_BLOB_NAME,
+    MANIFEST_BLOB_NAME,
 };
 use pbs_datastore::read_chunk::AsyncReadChunk;
 use pbs_datastore::{
@@ -337,7 +338,7 @@ impl PullSource for LocalSource {
     ) -> Result<Vec<BackupDir>, Error> {
         Ok(self
             .store
-            .backup_group(namespace.clone(), group.clone())
+            .backup_group(namespace.clone(), group.clone())?
             .iter_snapshots()?
             .filter_map(Result::ok)
             .map(|snapshot| snapshot.dir().to_owned())
@@ -579,12 +580,12 @@ impl PullParameters {
             })
         } else {
             Arc::new(LocalSource {
-                store: DataStore::lookup_datastore(remote_store, Some(Operation::Read))?,
+                store: DataStore::lookup_datastore(remote_store, Some(Operation::Read), None)?,
                 ns: remote_ns,
             })
         };
         let target = PullTarget {
-            store: DataStore::lookup_datastore(store, Some(Operation::Write))?,
+            store: DataStore::lookup_datastore(store, Some(Operation::Write), None)?,
             ns,
         };
 
@@ -705,17 +706,17 @@ async fn pull_index_chunks<I: IndexFile>(
 }
 
 fn verify_archive(info: &FileInfo, csum: &[u8; 32], size: u64) -> Result<(), Error> {
-    if size != info.size {
+    if size != info.size() {
         bail!(
             "wrong size for file '{}' ({} != {})",
-            info.filename,
-            info.size,
+            info.filename(),
+            info.size(),
             size
         );
     }
 
-    if csum != &info.csum {
-        bail!("wrong checksum for file '{}'", info.filename);
+    if csum != info.csum() {
+        bail!("wrong checksum for file '{}'", info.filename());
     }
 
     Ok(())
@@ -736,7 +737,7 @@ async fn pull_single_archive<'a>(
     archive_info: &'a FileInfo,
     downloaded_chunks: Arc<Mutex<HashSet<[u8; 32]>>>,
 ) -> Result<PullStats, Error> {
-    let archive_name = &archive_info.filename;
+    let archive_name = archive_info.filename();
     let mut path = snapshot.full_path();
     path.push(archive_name);
 
@@ -766,7 +767,7 @@ async fn pull_single_archive<'a>(
             } else {
                 let stats = pull_index_chunks(
                     worker,
-                    reader.chunk_reader(archive_info.crypt_mode),
+                    reader.chunk_reader(archive_info.crypt_mode()),
                     snapshot.datastore().clone(),
                     index,
                     downloaded_chunks,
@@ -787,7 +788,7 @@ async fn pull_single_archive<'a>(
             } else {
                 let stats = pull_index_chunks(
                     worker,
-                    reader.chunk_reader(archive_info.crypt_mode),
+                    reader.chunk_reader(archive_info.crypt_mode()),
                     snapshot.datastore().clone(),
                     index,
                     downloaded_chunks,
@@ -871,15 +872,18 @@ async fn pull_snapshot<'a>(
 
     for item in manifest.files() {
         let mut path = snapshot.full_path();
-        path.push(&item.filename);
+        path.push(item.filename());
 
         if path.exists() {
-            match archive_type(&item.filename)? {
+            match archive_type(item.filename())? {
                 ArchiveType::DynamicIndex => {
                     let index = DynamicIndexReader::open(&path)?;
                     let (csum, size) = index.compute_csum();
-                    match manifest.verify_file(&item.filename, &csum, size) {
-                        Ok(_) => continue,
+                    match manifest.verify_file(item.filename(), &csum, size) {
+                        Ok(VerifyFileResult::Ok) => continue,
+                        Ok(mismatch) => {
+                            task_log!(worker, "detected changed file {:?} - {}", path, mismatch);
+                        }
                         Err(err) => {
                             task_log!(worker, "detected changed file {:?} - {}", path, err);
                         }
@@ -888,8 +892,11 @@ async fn pull_snapshot<'a>(
                 ArchiveType::FixedIndex => {
                     let index = FixedIndexReader::open(&path)?;
                     let (csum, size) = index.compute_csum();
-                    match manifest.verify_file(&item.filename, &csum, size) {
-                        Ok(_) => continue,
+                    match manifest.verify_file(item.filename(), &csum, size) {
+                        Ok(VerifyFileResult::Ok) => continue,
+                        Ok(mismatch) => {
+                            task_log!(worker, "detected changed file {:?} - {}", path, mismatch);
+                        }
                         Err(err) => {
                             task_log!(worker, "detected changed file {:?} - {}", path, err);
                         }
@@ -898,8 +905,11 @@ async fn pull_snapshot<'a>(
                 ArchiveType::Blob => {
                     let mut tmpfile = std::fs::File::open(&path)?;
                     let (csum, size) = sha256(&mut tmpfile)?;
-                    match manifest.verify_file(&item.filename, &csum, size) {
-                        Ok(_) => continue,
+                    match manifest.verify_file(item.filename(), &csum, size) {
+                        Ok(VerifyFileResult::Ok) => continue,
+                        Ok(mismatch) => {
+                            task_log!(worker, "detected changed file {:?} - {}", path, mismatch);
+                        }
                         Err(err) => {
                             task_log!(worker, "detected changed file {:?} - {}", path, err);
                         }
@@ -945,9 +955,11 @@ async fn pull_snapshot_from<'a>(
     snapshot: &'a pbs_datastore::BackupDir,
     downloaded_chunks: Arc<Mutex<HashSet<[u8; 32]>>>,
 ) -> Result<PullStats, Error> {
-    let (_path, is_new, _snap_lock) = snapshot
-        .datastore()
-        .create_locked_backup_dir(snapshot.backup_ns(), snapshot.as_ref())?;
+    let (_path, is_new, _snap_lock) = snapshot.datastore().create_locked_backup_dir(
+        snapshot.backup_ns(),
+        snapshot.as_ref(),
+        true,
+    )?;
 
     let pull_stats = if is_new {
         task_log!(worker, "sync snapshot {}", snapshot.dir());
@@ -1161,7 +1173,7 @@ async fn pull_group(
         let group = params
             .target
             .store
-            .backup_group(target_ns.clone(), group.clone());
+            .backup_group(target_ns.clone(), group.clone())?;
         let local_list = group.list_backups()?;
         for info in local_list {
             let snapshot = info.backup_dir;
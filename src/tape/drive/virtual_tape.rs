@@ -295,7 +295,7 @@ impl TapeDriver for VirtualTapeHandle {
                 })?;
 
                 let reader = EmulateTapeReader::new(file);
-                let reader = BlockedReader::open(reader)?;
+                let reader = BlockedReader::open(reader, None)?;
                 Ok(Box::new(reader))
             }
             None => Err(BlockReadError::Error(proxmox_lang::io_format_err!(
@@ -351,7 +351,7 @@ impl TapeDriver for VirtualTapeHandle {
                 }
 
                 let writer = EmulateTapeWriter::new(file, free_space);
-                let writer = Box::new(BlockedWriter::new(writer));
+                let writer = Box::new(BlockedWriter::new(writer, None));
 
                 Ok(writer)
             }
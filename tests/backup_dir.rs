@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use pbs_datastore::BackupDir;
+
+#[test]
+fn test_backup_dir_clone_shares_datastore() {
+    let backup_dir = BackupDir::new_test("host/elsa/2019-12-02T11:59:10Z".parse().unwrap());
+    assert_eq!(Arc::strong_count(backup_dir.datastore()), 1);
+
+    let cloned = backup_dir.clone();
+
+    assert!(Arc::ptr_eq(backup_dir.datastore(), cloned.datastore()));
+    assert_eq!(Arc::strong_count(backup_dir.datastore()), 2);
+    assert_eq!(backup_dir.relative_path(), cloned.relative_path());
+
+    drop(cloned);
+    assert_eq!(Arc::strong_count(backup_dir.datastore()), 1);
+}
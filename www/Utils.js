@@ -443,6 +443,7 @@ Ext.define('PBS.Utils', {
 
 	Proxmox.Utils.overrideNotificationFieldValue({
 	    'acme': gettext('ACME certificate renewal'),
+	    'datastore-capacity': gettext('Datastore running full'),
 	    'gc': gettext('Garbage collection'),
 	    'package-updates': gettext('Package updates are available'),
 	    'prune': gettext('Prune job'),